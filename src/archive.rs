@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Component, Path};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thousands::Separable;
+use zip::write::SimpleFileOptions;
+
+use crate::cli::Cli;
+use crate::formatters::Sink;
+use crate::languages::LanguageOverrides;
+use crate::models::{ArchiveFormat, TokenizerModel};
+use crate::select_files;
+use crate::session::Session;
+use crate::tokenizers;
+use crate::utils::PathRenderer;
+
+pub const MANIFEST_NAME: &str = "MANIFEST.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub language: Option<String>,
+    pub bytes: usize,
+    pub lines: usize,
+    // Binary files are archived as-is but not tokenized
+    pub tokens: usize,
+    pub sha256: String,
+}
+
+impl ManifestEntry {
+    pub fn new(
+        path: &str,
+        content: &[u8],
+        model: &TokenizerModel,
+        languages: &LanguageOverrides,
+    ) -> Self {
+        let text = std::str::from_utf8(content).ok();
+        Self {
+            path: path.to_string(),
+            language: languages.detect(Path::new(path), text).map(str::to_string),
+            bytes: content.len(),
+            lines: text.map_or(0, |text| text.lines().count()),
+            tokens: text.map_or(0, |text| tokenizers::count_tokens(text, model)),
+            sha256: Sha256::digest(content)
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub generator: String,
+    pub model: String,
+    pub total_tokens: usize,
+    pub total_bytes: usize,
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn new(model: &TokenizerModel) -> Self {
+        Self {
+            generator: format!("summarize {}", env!("CARGO_PKG_VERSION")),
+            model: model.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn add(&mut self, entry: ManifestEntry) {
+        self.total_tokens += entry.tokens;
+        self.total_bytes += entry.bytes;
+        self.files.push(entry);
+    }
+}
+
+// Archive members must be relative, so roots and `..` are dropped from displayed paths
+pub fn archive_name(path: &Path) -> Option<String> {
+    let parts: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+enum ArchiveWriter {
+    Tar(tar::Builder<Sink<'static>>),
+    Zip(zip::ZipWriter<File>),
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path, format: ArchiveFormat) -> Result<Self> {
+        Ok(match format {
+            ArchiveFormat::Tar => ArchiveWriter::Tar(tar::Builder::new(Sink::create(path)?)),
+            ArchiveFormat::Zip => {
+                let file = File::create(path)
+                    .with_context(|| format!("Failed to create archive {}", path.display()))?;
+                ArchiveWriter::Zip(zip::ZipWriter::new(file))
+            }
+        })
+    }
+
+    fn add(&mut self, name: &str, content: &[u8], modified: SystemTime) -> Result<()> {
+        let mtime = modified
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        match self {
+            ArchiveWriter::Tar(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(mtime);
+                header.set_cksum();
+                builder.append_data(&mut header, name, content)?;
+            }
+            ArchiveWriter::Zip(zip) => {
+                zip.start_file(name, SimpleFileOptions::default())?;
+                zip.write_all(content)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            ArchiveWriter::Tar(builder) => builder.into_inner()?.finish()?,
+            ArchiveWriter::Zip(zip) => {
+                zip.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Archives the same files, under the same displayed paths, as the concatenated output
+pub fn process_archive(archive: &Path, cli: &Cli, session: &Session) -> Result<()> {
+    let format = ArchiveFormat::from_path(archive)?;
+    let files = select_files(&cli.paths, cli, session)?;
+
+    if files.is_empty() {
+        return Err(anyhow!("No files selected for {}", archive.display()));
+    }
+
+    let mut renderer = PathRenderer::from_cli(cli)?;
+    let mut writer = ArchiveWriter::create(archive, format)?;
+    let mut manifest = Manifest::new(&cli.tokenizer_model);
+    let mut seen = HashSet::new();
+
+    for file in &files {
+        let Ok(content) = session.read(file) else {
+            // Skip this file but continue processing others
+            continue;
+        };
+        let Some(name) = archive_name(&renderer.render(file)) else {
+            continue;
+        };
+        if name == MANIFEST_NAME || !seen.insert(name.clone()) {
+            eprintln!(
+                "Warning: skipping {}, another file is already archived as {}",
+                file.display(),
+                name
+            );
+            continue;
+        }
+
+        let modified = match &session.snapshot {
+            Some(snapshot) => snapshot.time(),
+            None => std::fs::metadata(file)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(UNIX_EPOCH),
+        };
+        writer.add(&name, &content, modified)?;
+        manifest.add(ManifestEntry::new(
+            &name,
+            &content,
+            &cli.tokenizer_model,
+            &session.languages,
+        ));
+    }
+
+    let manifest_json = serde_json::to_string_pretty(&manifest)?;
+    writer.add(MANIFEST_NAME, manifest_json.as_bytes(), SystemTime::now())?;
+    writer.finish()?;
+
+    if let Some(path_map) = &cli.path_map {
+        renderer.write_mapping(path_map)?;
+    }
+
+    println!(
+        "Archived {} files ({} tokens) into {}",
+        manifest.files.len(),
+        manifest.total_tokens.separate_with_commas(),
+        archive.display()
+    );
+
+    Ok(())
+}
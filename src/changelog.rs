@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Result};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Cli;
+use crate::formatters::{print_path, Writer};
+use crate::git::{self, CommitInfo, RevisionRange};
+use crate::llm::{summarize_with_llm, CHANGELOG_MERGE_PROMPT, CHANGELOG_PROMPT};
+use crate::models::{OutputFormat, TokenizerModel};
+use crate::session::Session;
+use crate::tokenizers;
+use crate::{load_template, write_footer, write_header};
+
+// Reads the commits in `range` from the repository containing the first path
+pub fn collect_history(range: &RevisionRange, cli: &Cli) -> Result<Vec<CommitInfo>> {
+    let start = cli
+        .paths
+        .first()
+        .map(PathBuf::as_path)
+        .unwrap_or(Path::new("."));
+    let repo = git::open_repository(start)?;
+    let commits = git::commit_history(&repo, range, cli.changelog_diffs)?;
+
+    if commits.is_empty() {
+        return Err(anyhow!("No commits found in {}", describe_range(range)));
+    }
+    Ok(commits)
+}
+
+fn describe_range(range: &RevisionRange) -> String {
+    format!("{}..{}", range.from, range.to.as_deref().unwrap_or("HEAD"))
+}
+
+pub fn chunk_budget(cli: &Cli) -> usize {
+    cli.chunk_tokens
+        .unwrap_or_else(|| tokenizers::get_context_window(&cli.tokenizer_model) / 2)
+}
+
+// Groups the rendered commits, oldest first, into chunks of at most `max_tokens`. A commit
+// too large on its own drops its diff, and gets a chunk to itself if it still doesn't fit
+pub fn chunk_history(
+    commits: &[CommitInfo],
+    max_tokens: usize,
+    model: &TokenizerModel,
+) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+
+    for commit in commits {
+        let mut text = commit.render(true);
+        let mut tokens = tokenizers::count_tokens(&text, model);
+        if tokens > max_tokens && commit.diff.is_some() {
+            text = commit.render(false);
+            tokens = tokenizers::count_tokens(&text, model);
+        }
+        if tokens > max_tokens {
+            eprintln!(
+                "Warning: commit {} alone exceeds the chunk budget ({} > {} tokens)",
+                commit.short_id(),
+                tokens,
+                max_tokens
+            );
+        }
+
+        if !current.is_empty() && current_tokens + tokens > max_tokens {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&text);
+        current_tokens += tokens;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+// The history for --changelog, one document per chunk so it shows how requests are split
+pub fn write_history(
+    range: &str,
+    cli: &Cli,
+    session: &Session,
+    output_format: &OutputFormat,
+    writer: &mut Writer,
+) -> Result<()> {
+    let range = RevisionRange::parse(range)?;
+    let commits = collect_history(&range, cli)?;
+    let chunks = chunk_history(&commits, chunk_budget(cli), &cli.tokenizer_model);
+
+    let template = load_template(cli, output_format)?;
+    write_header(writer, output_format, template.as_ref(), chunks.len())?;
+    for (index, chunk) in chunks.iter().enumerate() {
+        print_path(
+            writer,
+            Path::new(&format!("commits-{}.log", index + 1)),
+            chunk,
+            None,
+            output_format,
+            cli.line_numbers,
+            &cli.tokenizer_model,
+            &session.languages,
+            template.as_ref(),
+        )?;
+    }
+    write_footer(writer, output_format, template.as_ref(), chunks.len())
+}
+
+// `## TO - date`, or `## Unreleased - date` for history up to HEAD
+pub fn changelog_heading(range: &RevisionRange, commits: &[CommitInfo]) -> String {
+    let date = commits.last().map(CommitInfo::date).unwrap_or_default();
+    format!(
+        "## {} - {}",
+        range.to.as_deref().unwrap_or("Unreleased"),
+        date
+    )
+}
+
+// Release notes for `range`: one request per chunk, then one more to merge the partial entries
+pub fn summarize_history(range: &str, cli: &Cli, api_key: &str) -> Result<String> {
+    let range = RevisionRange::parse(range)?;
+    let commits = collect_history(&range, cli)?;
+    let chunks = chunk_history(&commits, chunk_budget(cli), &cli.tokenizer_model);
+    let prompt = cli.custom_prompt.as_deref().unwrap_or(CHANGELOG_PROMPT);
+
+    println!(
+        "Writing release notes for {} commits in {} request(s)",
+        commits.len(),
+        chunks.len() + usize::from(chunks.len() > 1)
+    );
+
+    let mut notes = Vec::new();
+    for chunk in chunks {
+        notes.push(summarize_with_llm(
+            Cursor::new(chunk),
+            prompt,
+            &cli.tokenizer_model,
+            api_key,
+        )?);
+    }
+
+    let body = if notes.len() == 1 {
+        notes.remove(0)
+    } else {
+        let partials = notes
+            .iter()
+            .enumerate()
+            .map(|(index, note)| format!("Part {}:\n\n{}", index + 1, note.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        summarize_with_llm(
+            Cursor::new(partials),
+            CHANGELOG_MERGE_PROMPT,
+            &cli.tokenizer_model,
+            api_key,
+        )?
+    };
+
+    Ok(format!(
+        "{}\n\n{}\n",
+        changelog_heading(&range, &commits),
+        body.trim()
+    ))
+}
+
+// Puts `entry` above the existing entries, below the file's `# ` title if it has one
+pub fn prepend_entry(existing: &str, entry: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("# Changelog\n\n{}", entry);
+    }
+
+    let title_end = match existing.lines().next() {
+        Some(first) if first.starts_with("# ") => first.len(),
+        _ => return format!("{}\n{}", entry, existing),
+    };
+    let (title, rest) = existing.split_at(title_end);
+    format!(
+        "{}\n\n{}\n{}",
+        title,
+        entry,
+        rest.trim_start_matches(['\r', '\n'])
+    )
+}
+
+pub fn write_changelog(path: &Path, entry: &str) -> Result<()> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    std::fs::write(path, prepend_entry(&existing, entry))?;
+    Ok(())
+}
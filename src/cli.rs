@@ -1,7 +1,9 @@
 use clap::Parser;
 use std::path::PathBuf;
 
-use crate::models::{OutputFormat, TokenizerModel};
+use crate::models::{
+    Extractor, OutputFormat, PathMode, Priority, ReportFormat, TokenizerModel, Transform,
+};
 
 #[derive(Parser, Debug, Clone)]
 #[command(
@@ -27,6 +29,70 @@ pub struct Cli {
     #[arg(long = "ignore-files-only")]
     pub ignore_files_only: bool,
 
+    /// Only include files changed on this branch since it forked from REF, plus uncommitted work
+    #[arg(long = "changed-since", value_name = "REF", conflicts_with_all = ["staged", "uncommitted"])]
+    pub changed_since: Option<String>,
+
+    /// Only include files with staged changes, with their content as staged
+    #[arg(long = "staged", conflicts_with = "uncommitted")]
+    pub staged: bool,
+
+    /// Only include files with staged, unstaged or untracked changes
+    #[arg(long = "uncommitted")]
+    pub uncommitted: bool,
+
+    /// Read files from this git commit, tag or branch instead of the working tree, honoring the
+    /// .gitignore files committed there
+    #[arg(
+        long = "rev",
+        value_name = "REV",
+        conflicts_with_all = ["changed_since", "staged", "uncommitted", "git_diff", "changelog"]
+    )]
+    pub rev: Option<String>,
+
+    /// Add each file's last modified date, commit count and number of authors from git history
+    #[arg(long = "git-metadata")]
+    pub git_metadata: bool,
+
+    /// Put the most active files first, ranked by commit count, recency or number of authors
+    #[arg(long = "priority", value_enum)]
+    pub priority: Option<Priority>,
+
+    /// Output unified diffs for a revision range (FROM, FROM..TO or FROM...TO) instead of whole
+    /// files; without TO the working tree is compared
+    #[arg(
+        long = "git-diff",
+        value_name = "RANGE",
+        conflicts_with_all = ["changed_since", "staged", "uncommitted", "archive", "count_tokens"]
+    )]
+    pub git_diff: Option<String>,
+
+    /// Lines of context around each change in --git-diff output
+    #[arg(long = "diff-context", default_value_t = 3, requires = "git_diff")]
+    pub diff_context: u32,
+
+    /// Also include the full post-change content of every file touched by --git-diff
+    #[arg(long = "diff-with-content", requires = "git_diff")]
+    pub diff_with_content: bool,
+
+    /// Summarize the commits in a revision range (FROM, FROM..TO) into release notes; without TO
+    /// the history up to HEAD is used
+    #[arg(
+        long = "changelog",
+        value_name = "RANGE",
+        conflicts_with_all = ["git_diff", "changed_since", "staged", "uncommitted", "archive", "count_tokens"]
+    )]
+    pub changelog: Option<String>,
+
+    /// Also include each commit's diff in the --changelog history
+    #[arg(long = "changelog-diffs", requires = "changelog")]
+    pub changelog_diffs: bool,
+
+    /// Maximum tokens of history per LLM request for --changelog [default: half the model's
+    /// context window]
+    #[arg(long = "chunk-tokens", requires = "changelog")]
+    pub chunk_tokens: Option<usize>,
+
     /// Ignore .gitignore files and include all files
     #[arg(long = "ignore-gitignore")]
     pub ignore_gitignore: bool,
@@ -43,18 +109,29 @@ pub struct Cli {
     #[arg(long = "ignore")]
     pub ignore_patterns: Vec<String>,
 
-    /// Output to a file instead of stdout
+    /// Output to a file instead of stdout (compressed when it ends in .gz or .zst)
     #[arg(short = 'o', long = "output")]
     pub output_file: Option<PathBuf>,
 
-    /// Output format
-    #[arg(
-        short = 'f',
-        long = "format",
-        value_enum,
-        default_value_t = OutputFormat::Default
-    )]
-    pub output_format: OutputFormat,
+    /// Also write the output to stdout when writing it to --output
+    #[arg(long = "tee", requires = "output_file")]
+    pub tee: bool,
+
+    /// Copy the output to the terminal's clipboard with OSC 52 (works over SSH)
+    #[arg(long = "clipboard")]
+    pub clipboard: bool,
+
+    /// Pack the selected files into a .tar, .tar.gz, .tar.zst or .zip with a JSON manifest
+    #[arg(long = "archive", value_name = "FILE", conflicts_with = "count_tokens")]
+    pub archive: Option<PathBuf>,
+
+    /// Output format [default: default]; --unpack detects it from the bundle unless given
+    #[arg(short = 'f', long = "format", value_enum)]
+    pub output_format: Option<OutputFormat>,
+
+    /// Template file defining header, per-file and footer layouts (implies --format template)
+    #[arg(long = "template", conflicts_with_all = ["cxml", "markdown"])]
+    pub template: Option<PathBuf>,
 
     /// Output in Claude XML format
     #[arg(short = 'c', long = "cxml", conflicts_with = "output_format")]
@@ -68,6 +145,92 @@ pub struct Cli {
     #[arg(short = 'n', long = "line-numbers")]
     pub line_numbers: bool,
 
+    /// Strip content to save tokens: comments, license, trailing-whitespace, blank-lines
+    /// (repeatable or comma-separated)
+    #[arg(long = "strip", value_enum, value_delimiter = ',', value_name = "WHAT")]
+    pub transforms: Vec<Transform>,
+
+    /// Reduce Rust, Python, JavaScript/TypeScript, Go and Java files to type definitions,
+    /// signatures and doc comments, with function bodies elided
+    #[arg(long = "outline")]
+    pub outline: bool,
+
+    /// Read files by type instead of including them raw: notebooks, tables, data, lockfiles
+    /// (repeatable or comma-separated)
+    #[arg(
+        long = "extract",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "WHAT"
+    )]
+    pub extractors: Vec<Extractor>,
+
+    /// Rows after the header that --extract tables keeps from CSV and TSV files
+    #[arg(long = "table-rows", default_value_t = 10, value_name = "ROWS")]
+    pub table_rows: usize,
+
+    /// Nesting depth at which --extract data collapses JSON and YAML
+    #[arg(long = "data-depth", default_value_t = 4, value_name = "DEPTH")]
+    pub data_depth: usize,
+
+    /// Output lines that --extract notebooks keeps for each notebook cell
+    #[arg(long = "cell-output-lines", default_value_t = 20, value_name = "LINES")]
+    pub cell_output_lines: usize,
+
+    /// Only output the definition of a symbol and the definitions it references, e.g.
+    /// 'crate::llm::summarize_with_llm' or 'Client.get' (repeatable)
+    #[arg(
+        long = "symbol",
+        value_name = "SYMBOL",
+        conflicts_with_all = ["git_diff", "changelog", "archive", "count_tokens", "outline"]
+    )]
+    pub symbols: Vec<String>,
+
+    /// How many levels of referenced definitions --symbol follows
+    #[arg(long = "symbol-depth", default_value_t = 2, value_name = "DEPTH")]
+    pub symbol_depth: usize,
+
+    /// Only include files reachable from this entry point through imports, e.g. src/main.rs
+    /// (repeatable)
+    #[arg(long = "entry", value_name = "FILE")]
+    pub entries: Vec<PathBuf>,
+
+    /// How many levels of imports --entry follows [default: no limit]
+    #[arg(long = "entry-depth", value_name = "DEPTH", requires = "entries")]
+    pub entry_depth: Option<usize>,
+
+    /// Override language detection for matching files, e.g. '*.tpl=jinja' (repeatable)
+    #[arg(long = "language", value_name = "PATTERN=LANGUAGE")]
+    pub language_overrides: Vec<String>,
+
+    /// Start the output with a directory tree of all included files
+    #[arg(long = "tree")]
+    pub tree: bool,
+
+    /// Annotate the directory tree with token counts and sizes
+    #[arg(long = "tree-stats", requires = "tree")]
+    pub tree_stats: bool,
+
+    /// How file paths are shown in the output
+    #[arg(long = "paths", value_enum, default_value_t = PathMode::Walked)]
+    pub path_mode: PathMode,
+
+    /// Directory that --paths relative is relative to (defaults to the current directory)
+    #[arg(long = "path-root")]
+    pub path_root: Option<PathBuf>,
+
+    /// Prefix added to every displayed path
+    #[arg(long = "path-prefix")]
+    pub path_prefix: Option<String>,
+
+    /// Write a JSON map from displayed paths to real paths; with --restore-paths, read it
+    #[arg(long = "path-map")]
+    pub path_map: Option<PathBuf>,
+
+    /// Print a text file (such as an LLM answer) with displayed paths mapped back to real ones
+    #[arg(long = "restore-paths", value_name = "FILE", requires = "path_map")]
+    pub restore_paths: Option<PathBuf>,
+
     /// Use NUL character as separator when reading from stdin
     #[arg(short = '0', long = "null")]
     pub null: bool,
@@ -80,8 +243,7 @@ pub struct Cli {
     #[arg(
         long = "model",
         value_enum,
-        default_value_t = TokenizerModel::Gemini15Flash,
-        requires = "count_tokens"
+        default_value_t = TokenizerModel::Gemini15Flash
     )]
     pub tokenizer_model: TokenizerModel,
 
@@ -101,20 +263,77 @@ pub struct Cli {
     #[arg(long = "show-cost", requires = "count_tokens")]
     pub show_cost: bool,
 
+    /// JSON pricing file overriding the built-in per-model prices
+    #[arg(long = "pricing")]
+    pub pricing_file: Option<PathBuf>,
+
+    /// Format of the token report
+    #[arg(
+        long = "report-format",
+        value_enum,
+        default_value_t = ReportFormat::Table,
+        requires = "count_tokens"
+    )]
+    pub report_format: ReportFormat,
+
+    /// Show how the content fits into the context window of every known model, counted with each model's tokenizer
+    #[arg(long = "fit-report", requires = "count_tokens")]
+    pub fit_report: bool,
+
+    /// Fail when the total token count exceeds this limit
+    #[arg(long = "max-tokens", requires = "count_tokens")]
+    pub max_tokens: Option<usize>,
+
+    /// Fail when any single file exceeds this many tokens
+    #[arg(long = "max-file-tokens", requires = "count_tokens")]
+    pub max_file_tokens: Option<usize>,
+
+    /// Fail when any directory below the input paths (including its subdirectories) exceeds this many tokens
+    #[arg(long = "max-dir-tokens", requires = "count_tokens")]
+    pub max_dir_tokens: Option<usize>,
+
     /// Only concatenate files without generating a summary
     #[arg(long = "no-summarize")]
     pub no_summarize: bool,
 
-    /// Custom prompt to use when generating a summary
+    /// Custom prompt to use when generating a summary (defaults to a codebase overview, a code
+    /// review with --git-diff or release notes with --changelog)
+    #[arg(long = "prompt")]
+    pub custom_prompt: Option<String>,
+
+    /// Output file for the summary [default: overview.md, review.md with --git-diff or
+    /// CHANGELOG.md with --changelog, where new entries are added at the top]
+    #[arg(long = "summary-output")]
+    pub summary_output: Option<PathBuf>,
+
+    /// Compare two saved JSON token reports and show per-file token deltas
+    #[arg(long = "diff-reports", num_args = 2, value_names = ["OLD", "NEW"])]
+    pub diff_reports: Vec<PathBuf>,
+
+    /// Count tokens at two git revisions of the inputs and show per-file token deltas
     #[arg(
-        long = "prompt",
-        default_value = "You are a senior software engineer reviewing a codebase. Generate a comprehensive overview.md file that explains the purpose, structure, and key components of this codebase. Focus on helping a new developer understand how the codebase is organized and how different parts work together."
+        long = "diff-revs",
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        conflicts_with_all = ["rev", "diff_reports"]
     )]
-    pub custom_prompt: String,
+    pub diff_revs: Vec<String>,
+
+    /// Recreate files from concatenated output (Default, Markdown, CXML or JSON); use - for stdin
+    #[arg(long = "unpack", value_name = "BUNDLE")]
+    pub unpack: Option<PathBuf>,
+
+    /// Directory to recreate unpacked files in
+    #[arg(long = "unpack-dir", default_value = ".", requires = "unpack")]
+    pub unpack_dir: PathBuf,
 
-    /// Output file for the summary
-    #[arg(long = "summary-output", default_value = "overview.md")]
-    pub summary_output: PathBuf,
+    /// Show what --unpack would write without touching any files
+    #[arg(long = "dry-run", requires = "unpack")]
+    pub dry_run: bool,
+
+    /// Show a unified diff against existing files when unpacking
+    #[arg(long = "diff", requires = "unpack")]
+    pub diff: bool,
 
     /// List available models from the LLM service
     #[arg(long = "list-models")]
@@ -124,3 +343,22 @@ pub struct Cli {
     #[arg(long = "threads", default_value = "0")]
     pub num_threads: usize,
 }
+
+impl Cli {
+    // The format picked with --format, --cxml, --markdown or --template, if any
+    pub fn chosen_format(&self) -> Option<OutputFormat> {
+        if self.cxml {
+            Some(OutputFormat::Cxml)
+        } else if self.markdown {
+            Some(OutputFormat::Markdown)
+        } else if self.template.is_some() {
+            Some(OutputFormat::Template)
+        } else {
+            self.output_format.clone()
+        }
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.chosen_format().unwrap_or(OutputFormat::Default)
+    }
+}
@@ -0,0 +1,196 @@
+use serde::de::IgnoredAny;
+
+// Pretty-prints JSON with two-space indentation, collapsing objects and arrays nested `depth`
+// levels deep into `{... 3 keys}` or `[... 40 items]`. Keys keep their order in the file.
+// Returns None for content that isn't valid JSON, such as JSON with comments
+pub fn truncate_json(content: &str, depth: usize) -> Option<String> {
+    serde_json::from_str::<IgnoredAny>(content).ok()?;
+    let mut printer = JsonPrinter {
+        input: content,
+        position: 0,
+        output: String::with_capacity(content.len()),
+        max_depth: depth,
+    };
+    printer.value(0);
+    printer.output.push('\n');
+    Some(printer.output)
+}
+
+// Walks JSON that is already known to be valid, so it never has to report errors
+struct JsonPrinter<'a> {
+    input: &'a str,
+    position: usize,
+    output: String,
+    max_depth: usize,
+}
+
+impl JsonPrinter<'_> {
+    fn peek(&self) -> u8 {
+        self.input.as_bytes()[self.position]
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.position < self.input.len() && self.peek().is_ascii_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    fn value(&mut self, depth: usize) {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek() {
+            b'{' | b'[' => return self.container(depth),
+            b'"' => self.skip_string(),
+            _ => {
+                while self.position < self.input.len()
+                    && !matches!(self.peek(), b',' | b'}' | b']')
+                    && !self.peek().is_ascii_whitespace()
+                {
+                    self.position += 1;
+                }
+            }
+        }
+        let input = self.input;
+        self.output.push_str(&input[start..self.position]);
+    }
+
+    fn container(&mut self, depth: usize) {
+        let open = self.peek();
+        let (close, object) = match open {
+            b'{' => ('}', true),
+            _ => (']', false),
+        };
+        self.position += 1;
+        self.skip_whitespace();
+
+        if self.peek() as char == close {
+            self.position += 1;
+            self.output.push(open as char);
+            self.output.push(close);
+            return;
+        }
+        if depth >= self.max_depth {
+            let count = self.skip_container();
+            let unit = match (object, count) {
+                (true, 1) => "key",
+                (true, _) => "keys",
+                (false, 1) => "item",
+                (false, _) => "items",
+            };
+            self.output
+                .push_str(&format!("{}... {} {}{}", open as char, count, unit, close));
+            return;
+        }
+
+        self.output.push(open as char);
+        let indent = "  ".repeat(depth + 1);
+        loop {
+            self.output.push('\n');
+            self.output.push_str(&indent);
+            if object {
+                self.skip_whitespace();
+                let start = self.position;
+                self.skip_string();
+                let input = self.input;
+                self.output.push_str(&input[start..self.position]);
+                self.skip_whitespace();
+                // The colon
+                self.position += 1;
+                self.output.push_str(": ");
+            }
+            self.value(depth + 1);
+            self.skip_whitespace();
+            let separator = self.peek();
+            self.position += 1;
+            if separator != b',' {
+                break;
+            }
+            self.output.push(',');
+        }
+        self.output.push('\n');
+        self.output.push_str(&"  ".repeat(depth));
+        self.output.push(close);
+    }
+
+    fn skip_string(&mut self) {
+        self.position += 1;
+        while self.peek() != b'"' {
+            if self.peek() == b'\\' {
+                self.position += 1;
+            }
+            self.position += 1;
+        }
+        self.position += 1;
+    }
+
+    // Skips the rest of a non-empty container whose opening bracket was consumed, and counts
+    // its entries
+    fn skip_container(&mut self) -> usize {
+        let (mut nesting, mut entries) = (0, 1);
+        loop {
+            match self.peek() {
+                b'"' => {
+                    self.skip_string();
+                    continue;
+                }
+                b'{' | b'[' => nesting += 1,
+                b'}' | b']' if nesting == 0 => {
+                    self.position += 1;
+                    return entries;
+                }
+                b'}' | b']' => nesting -= 1,
+                b',' if nesting == 0 => entries += 1,
+                _ => {}
+            }
+            self.position += 1;
+        }
+    }
+}
+
+// Drops block-style YAML lines nested `depth` levels deep, judged by indentation, and leaves a
+// `# ... 12 more lines` comment where they were. Flow-style collections stay on their line
+pub fn truncate_yaml(content: &str, depth: usize) -> String {
+    let mut result = String::with_capacity(content.len());
+    // Indentation of the lines enclosing the current one
+    let mut enclosing: Vec<usize> = Vec::new();
+    // Indentation and line count of the run of lines being dropped
+    let mut dropped: Option<(usize, usize)> = None;
+
+    let flush = |result: &mut String, dropped: &mut Option<(usize, usize)>| {
+        if let Some((indent, lines)) = dropped.take() {
+            let unit = if lines == 1 { "line" } else { "lines" };
+            result.push_str(&format!(
+                "{}# ... {} more {}\n",
+                " ".repeat(indent),
+                lines,
+                unit
+            ));
+        }
+    };
+
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_start();
+        if text.trim().is_empty() || text.starts_with('#') {
+            if dropped.is_none() {
+                result.push_str(line);
+            }
+            continue;
+        }
+        let indent = line.len() - text.len();
+        while enclosing.last().is_some_and(|&outer| outer >= indent) {
+            enclosing.pop();
+        }
+        let level = enclosing.len();
+        enclosing.push(indent);
+
+        if level >= depth {
+            let (_, lines) = dropped.get_or_insert((indent, 0));
+            *lines += 1;
+            continue;
+        }
+        flush(&mut result, &mut dropped);
+        result.push_str(line);
+    }
+    flush(&mut result, &mut dropped);
+    result
+}
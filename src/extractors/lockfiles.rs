@@ -0,0 +1,232 @@
+use serde_json::Value;
+
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "uv.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+
+pub fn is_lockfile(name: &str) -> bool {
+    LOCKFILES.contains(&name)
+}
+
+// Replaces a lockfile with the number of packages it pins and one `name version` line per
+// package, sorted by name
+pub fn summarize_lockfile(name: &str, content: &str) -> Option<String> {
+    let packages = lockfile_packages(name, content)?;
+    let mut summary = format!("{} packages\n", packages.len());
+    for (package, version) in packages {
+        summary.push_str(&package);
+        if !version.is_empty() {
+            summary.push(' ');
+            summary.push_str(&version);
+        }
+        summary.push('\n');
+    }
+    Some(summary)
+}
+
+// The (name, version) pairs pinned by a lockfile, sorted and without duplicates. None when the
+// file name isn't a known lockfile or a JSON lockfile doesn't parse
+pub fn lockfile_packages(name: &str, content: &str) -> Option<Vec<(String, String)>> {
+    let mut packages = match name {
+        "Cargo.lock" | "poetry.lock" | "uv.lock" => toml_packages(content),
+        "package-lock.json" | "npm-shrinkwrap.json" => npm_packages(content)?,
+        "composer.lock" => composer_packages(content)?,
+        "Pipfile.lock" => pipfile_packages(content)?,
+        "yarn.lock" => yarn_packages(content),
+        "pnpm-lock.yaml" => pnpm_packages(content),
+        "Gemfile.lock" => gemfile_packages(content),
+        "go.sum" => go_sum_packages(content),
+        _ => return None,
+    };
+    packages.retain(|(name, _)| !name.is_empty());
+    packages.sort();
+    packages.dedup();
+    Some(packages)
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).to_string()
+}
+
+// `[[package]]` tables with `name` and `version` keys
+fn toml_packages(content: &str) -> Vec<(String, String)> {
+    let mut packages: Vec<(String, String)> = Vec::new();
+    let mut in_package = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[[package]]";
+            if in_package {
+                packages.push(Default::default());
+            }
+            continue;
+        }
+        let (Some(package), Some((key, value))) = (packages.last_mut(), line.split_once('='))
+        else {
+            continue;
+        };
+        match (in_package, key.trim()) {
+            (true, "name") => package.0 = unquote(value),
+            (true, "version") => package.1 = unquote(value),
+            _ => {}
+        }
+    }
+    packages
+}
+
+fn json_version(value: &Value) -> String {
+    value["version"].as_str().unwrap_or_default().to_string()
+}
+
+// lockfileVersion 2 and 3 list `node_modules/...` paths under "packages"; version 1 nests
+// "dependencies" by name
+fn npm_packages(content: &str) -> Option<Vec<(String, String)>> {
+    let lockfile: Value = serde_json::from_str(content).ok()?;
+    if let Some(packages) = lockfile["packages"].as_object() {
+        return Some(
+            packages
+                .iter()
+                .filter_map(|(path, package)| {
+                    let (_, name) = path.rsplit_once("node_modules/")?;
+                    Some((name.to_string(), json_version(package)))
+                })
+                .collect(),
+        );
+    }
+    let mut packages = Vec::new();
+    let mut pending = vec![&lockfile["dependencies"]];
+    while let Some(dependencies) = pending.pop() {
+        for (name, package) in dependencies.as_object().into_iter().flatten() {
+            packages.push((name.clone(), json_version(package)));
+            pending.push(&package["dependencies"]);
+        }
+    }
+    Some(packages)
+}
+
+fn composer_packages(content: &str) -> Option<Vec<(String, String)>> {
+    let lockfile: Value = serde_json::from_str(content).ok()?;
+    let packages = ["packages", "packages-dev"]
+        .iter()
+        .filter_map(|section| lockfile[section].as_array())
+        .flatten()
+        .map(|package| {
+            let name = package["name"].as_str().unwrap_or_default();
+            (name.to_string(), json_version(package))
+        })
+        .collect();
+    Some(packages)
+}
+
+fn pipfile_packages(content: &str) -> Option<Vec<(String, String)>> {
+    let lockfile: Value = serde_json::from_str(content).ok()?;
+    let packages = ["default", "develop"]
+        .iter()
+        .filter_map(|section| lockfile[section].as_object())
+        .flatten()
+        .map(|(name, package)| {
+            let version = json_version(package);
+            (name.clone(), version.trim_start_matches("==").to_string())
+        })
+        .collect();
+    Some(packages)
+}
+
+// `@scope/name@^1.0` and `name@npm:^1.0` both name the package before the last `@` that
+// doesn't start the name
+fn package_name(spec: &str) -> &str {
+    match spec.get(1..).and_then(|rest| rest.find('@')) {
+        Some(at) => &spec[..at + 1],
+        None => spec,
+    }
+}
+
+// Entries start with unindented `"a@^1", "a@^1.2":` lines, followed by an indented version:
+// `version "1.2.0"` in yarn 1 and `version: 1.2.0` in later versions
+fn yarn_packages(content: &str) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    let mut entry: Option<String> = None;
+    for line in content.lines() {
+        if !line.starts_with([' ', '#']) && line.ends_with(':') {
+            let spec = line
+                .trim_end_matches(':')
+                .split(',')
+                .next()
+                .unwrap_or_default();
+            let name = package_name(&unquote(spec)).to_string();
+            entry = (name != "__metadata").then_some(name);
+        } else if let (Some(name), Some(version)) = (&entry, line.trim().strip_prefix("version")) {
+            packages.push((name.clone(), unquote(version.trim_start_matches(':'))));
+            entry = None;
+        }
+    }
+    packages
+}
+
+// Keys of the top-level "packages" mapping: `/name/1.0.0` before lockfile version 6,
+// `/name@1.0.0` after, without the leading slash from version 9, and with peer dependencies
+// in parentheses
+fn pnpm_packages(content: &str) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        let is_entry = line.starts_with("  ") && !line.starts_with("   ");
+        if !in_packages || !is_entry || !line.trim_end().ends_with(':') {
+            continue;
+        }
+        let key = unquote(line.trim().trim_end_matches(':'));
+        let key = key.trim_start_matches('/');
+        let key = key.split('(').next().unwrap_or_default();
+        let name = package_name(key);
+        let package = match key
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('@'))
+        {
+            Some(version) => (name.to_string(), version.to_string()),
+            None => match key.rsplit_once('/') {
+                Some((name, version)) => (name.to_string(), version.to_string()),
+                None => (key.to_string(), String::new()),
+            },
+        };
+        packages.push(package);
+    }
+    packages
+}
+
+// `    name (1.0.0)` lines under `specs:`; their dependencies are indented further
+fn gemfile_packages(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter(|line| line.starts_with("    ") && !line.starts_with("     "))
+        .filter_map(|line| {
+            let (name, version) = line.trim().split_once(" (")?;
+            Some((name.to_string(), version.trim_end_matches(')').to_string()))
+        })
+        .collect()
+}
+
+// `module version hash` lines, listed twice when the go.mod file has its own hash
+fn go_sum_packages(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let module = fields.next()?;
+            let version = fields.next()?.trim_end_matches("/go.mod");
+            Some((module.to_string(), version.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+mod data;
+mod lockfiles;
+mod notebooks;
+mod tables;
+
+pub use data::{truncate_json, truncate_yaml};
+pub use lockfiles::{is_lockfile, lockfile_packages, summarize_lockfile};
+pub use notebooks::render_notebook;
+pub use tables::sample_table;
+
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::models::Extractor;
+
+// How much of each file type --extract keeps
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractOptions {
+    pub table_rows: usize,
+    pub data_depth: usize,
+    pub cell_output_lines: usize,
+}
+
+impl ExtractOptions {
+    pub fn from_cli(cli: &Cli) -> Self {
+        ExtractOptions {
+            table_rows: cli.table_rows,
+            data_depth: cli.data_depth,
+            cell_output_lines: cli.cell_output_lines,
+        }
+    }
+}
+
+// Reads the file with the first enabled extractor for its type. Lockfiles are checked before
+// data files, since package-lock.json and pnpm-lock.yaml are both. Returns None when no
+// extractor applies or the content isn't valid for its type, so the file is included as is
+pub fn extract(
+    content: &str,
+    path: &Path,
+    extractors: &[Extractor],
+    options: &ExtractOptions,
+) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    let candidates = [
+        (Extractor::Lockfiles, is_lockfile(name)),
+        (Extractor::Notebooks, extension == "ipynb"),
+        (
+            Extractor::Tables,
+            matches!(extension.as_str(), "csv" | "tsv"),
+        ),
+        (
+            Extractor::Data,
+            matches!(extension.as_str(), "json" | "yaml" | "yml"),
+        ),
+    ];
+    let extractor = candidates
+        .into_iter()
+        .find(|(extractor, matches)| *matches && extractors.contains(extractor))?
+        .0;
+
+    match extractor {
+        Extractor::Lockfiles => summarize_lockfile(name, content),
+        Extractor::Notebooks => render_notebook(content, options.cell_output_lines),
+        Extractor::Tables => Some(sample_table(
+            content,
+            options.table_rows,
+            extension == "csv",
+        )),
+        Extractor::Data if extension == "json" => truncate_json(content, options.data_depth),
+        Extractor::Data => Some(truncate_yaml(content, options.data_depth)),
+    }
+}
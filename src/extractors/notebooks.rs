@@ -0,0 +1,94 @@
+use serde_json::Value;
+
+// Renders a Jupyter notebook in the percent format: `# %%` before each code cell and
+// `# %% [markdown]` before each markdown cell. Cell outputs follow the code as comments, cut
+// to `output_lines` lines, and images or HTML are replaced by a note naming their type
+pub fn render_notebook(content: &str, output_lines: usize) -> Option<String> {
+    let notebook: Value = serde_json::from_str(content).ok()?;
+    let cells = notebook.get("cells")?.as_array()?;
+
+    let mut sections = Vec::new();
+    for cell in cells {
+        let source = text(&cell["source"]);
+        let source = source.trim_end();
+        match cell["cell_type"].as_str() {
+            Some("code") => {
+                let mut section = format!("# %%\n{}", source);
+                let output = truncate_lines(&outputs(cell), output_lines);
+                if !output.is_empty() {
+                    section.push_str("\n# Output:");
+                    for line in output.lines() {
+                        section.push_str("\n# ");
+                        section.push_str(line);
+                    }
+                }
+                sections.push(section);
+            }
+            Some(kind) => sections.push(format!("# %% [{}]\n{}", kind, source)),
+            None => {}
+        }
+    }
+    Some(sections.join("\n\n") + "\n")
+}
+
+// Multiline strings in notebooks are either a string or an array of lines
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn outputs(cell: &Value) -> String {
+    let Some(outputs) = cell["outputs"].as_array() else {
+        return String::new();
+    };
+    let mut result = String::new();
+    for output in outputs {
+        let text = match output["output_type"].as_str() {
+            Some("stream") => text(&output["text"]),
+            Some("execute_result" | "display_data") => rich_output(&output["data"]),
+            Some("error") => format!(
+                "{}: {}",
+                output["ename"].as_str().unwrap_or("Error"),
+                output["evalue"].as_str().unwrap_or_default()
+            ),
+            _ => continue,
+        };
+        result.push_str(text.trim_end_matches('\n'));
+        result.push('\n');
+    }
+    result
+}
+
+// The plain text form of a rich output, plus a note for each image. Outputs with neither,
+// like HTML-only tables, are noted by their first type
+fn rich_output(data: &Value) -> String {
+    let Some(data) = data.as_object() else {
+        return String::new();
+    };
+    let mut lines: Vec<String> = data.get("text/plain").map(text).into_iter().collect();
+    lines.extend(
+        data.keys()
+            .filter(|mime| mime.starts_with("image/"))
+            .map(|mime| format!("[{} output omitted]", mime)),
+    );
+    if lines.is_empty() {
+        if let Some(mime) = data.keys().next() {
+            lines.push(format!("[{} output omitted]", mime));
+        }
+    }
+    lines.join("\n")
+}
+
+fn truncate_lines(text: &str, limit: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= limit {
+        return text.to_string();
+    }
+    let more = format!("... {} more lines", lines.len() - limit);
+    let mut kept = lines[..limit].to_vec();
+    kept.push(&more);
+    kept.join("\n")
+}
@@ -0,0 +1,43 @@
+use thousands::Separable;
+
+// Keeps the header and the first `rows` records. In CSV (`quoted`), a quote at the start of a
+// field opens a quoted field that may hold newlines, and a doubled quote inside it is a literal
+// quote. Quotes anywhere else, and all quotes in TSV, are ordinary characters
+pub fn sample_table(content: &str, rows: usize, quoted: bool) -> String {
+    let bytes = content.as_bytes();
+    let mut record_ends = Vec::new();
+    let mut in_quotes = false;
+    let mut field_start = true;
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        index += 1;
+        if in_quotes {
+            if byte == b'"' {
+                match bytes.get(index) {
+                    Some(b'"') => index += 1,
+                    _ => in_quotes = false,
+                }
+            }
+            continue;
+        }
+        match byte {
+            b'"' if quoted && field_start => in_quotes = true,
+            b'\n' => record_ends.push(index),
+            _ => {}
+        }
+        field_start = matches!(byte, b',' | b'\n');
+    }
+    let unterminated = record_ends.last().copied().unwrap_or(0) < content.len();
+    let records = record_ends.len() + usize::from(unterminated);
+
+    let kept = rows + 1;
+    if records <= kept {
+        return content.to_string();
+    }
+    format!(
+        "{}... {} more rows\n",
+        &content[..record_ends[kept - 1]],
+        (records - kept).separate_with_commas()
+    )
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::engine::GeneralPurpose;
+use base64::write::EncoderWriter;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use thousands::Separable;
+
+// Several terminals (and tmux by default) silently drop OSC 52 payloads larger than this
+pub const CLIPBOARD_WARN_BYTES: usize = 100_000;
+
+// Copies everything written to it into the terminal's clipboard with the OSC 52 escape
+// sequence, which also works over SSH because the local terminal does the copying
+pub struct Clipboard {
+    encoder: EncoderWriter<'static, GeneralPurpose, Box<dyn Write>>,
+    bytes: usize,
+    tmux: bool,
+}
+
+impl Clipboard {
+    pub fn new() -> Result<Self> {
+        // Write to the terminal directly so stdout can still be piped or redirected
+        let mut terminal: Box<dyn Write> = match OpenOptions::new().write(true).open("/dev/tty") {
+            Ok(tty) => Box::new(tty),
+            Err(_) => Box::new(io::stderr()),
+        };
+
+        // tmux only forwards escape sequences to the outer terminal inside a passthrough
+        let tmux = std::env::var_os("TMUX").is_some();
+        if tmux {
+            terminal.write_all(b"\x1bPtmux;\x1b")?;
+        }
+        terminal.write_all(b"\x1b]52;c;")?;
+
+        Ok(Self {
+            encoder: EncoderWriter::new(terminal, &STANDARD),
+            bytes: 0,
+            tmux,
+        })
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        let mut terminal = self.encoder.finish()?;
+        terminal.write_all(b"\x07")?;
+        if self.tmux {
+            terminal.write_all(b"\x1b\\")?;
+        }
+        terminal.flush()?;
+
+        eprintln!(
+            "Copied {} bytes to the clipboard",
+            self.bytes.separate_with_commas()
+        );
+        if self.bytes > CLIPBOARD_WARN_BYTES {
+            eprintln!(
+                "Warning: many terminals ignore clipboard copies over {} bytes; if nothing was copied, use --output instead",
+                CLIPBOARD_WARN_BYTES.separate_with_commas()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Write for Clipboard {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.encoder.write(buf)?;
+        self.bytes += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
@@ -1,5 +1,15 @@
+mod clipboard;
 mod output;
+mod template;
+mod tree;
 mod writer;
 
-pub use output::{add_line_numbers, print_as_markdown, print_as_xml, print_default, print_path};
-pub use writer::Writer;
+pub use clipboard::{Clipboard, CLIPBOARD_WARN_BYTES};
+pub use output::{
+    add_line_numbers, add_line_numbers_from, escape_xml, print_as_json, print_as_jsonl,
+    print_as_markdown, print_as_xml, print_default, print_path, print_with_template, relative_path,
+    wrap_cdata,
+};
+pub use template::{render_template, Template};
+pub use tree::{print_tree, render_tree, TreeEntry};
+pub use writer::{Sink, Writer};
@@ -1,62 +1,102 @@
-use anyhow::Result;
-use lazy_static::lazy_static;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::Path;
 
+use super::template::{render_template, Template};
 use super::Writer;
-use crate::models::OutputFormat;
-
-// Maps file extensions to language names for markdown formatting
-lazy_static! {
-    static ref EXT_TO_LANG: HashMap<&'static str, &'static str> = {
-        let mut m = HashMap::new();
-        m.insert("py", "python");
-        m.insert("c", "c");
-        m.insert("cpp", "cpp");
-        m.insert("h", "c");
-        m.insert("hpp", "cpp");
-        m.insert("java", "java");
-        m.insert("js", "javascript");
-        m.insert("ts", "typescript");
-        m.insert("html", "html");
-        m.insert("css", "css");
-        m.insert("xml", "xml");
-        m.insert("json", "json");
-        m.insert("yaml", "yaml");
-        m.insert("yml", "yaml");
-        m.insert("sh", "bash");
-        m.insert("rb", "ruby");
-        m.insert("rs", "rust");
-        m.insert("go", "go");
-        m.insert("md", "markdown");
-        m.insert("toml", "toml");
-        m
-    };
+use crate::languages::LanguageOverrides;
+use crate::models::{FileHistory, OutputFormat, TokenizerModel};
+use crate::tokenizers;
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    path: String,
+    language: Option<&'a str>,
+    bytes: usize,
+    tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history: Option<FileHistory>,
+    content: &'a str,
 }
 
 pub fn add_line_numbers(content: &str) -> String {
+    add_line_numbers_from(content, 1)
+}
+
+// Numbers lines starting at `first`, for excerpts that begin partway through a file
+pub fn add_line_numbers_from(content: &str, first: usize) -> String {
     let lines: Vec<&str> = content.lines().collect();
-    let padding = lines.len().to_string().len();
+    let padding = (first + lines.len().saturating_sub(1)).to_string().len();
 
     lines
         .iter()
         .enumerate()
-        .map(|(i, line)| format!("{:padding$}  {}", i + 1, line, padding = padding))
+        .map(|(i, line)| format!("{:padding$}  {}", i + first, line, padding = padding))
         .collect::<Vec<String>>()
         .join("\n")
 }
 
+// `history` is the file's git history, shown with --git-metadata
+#[allow(clippy::too_many_arguments)]
 pub fn print_path(
     writer: &mut Writer,
     path: &Path,
     content: &str,
+    history: Option<FileHistory>,
     format: &OutputFormat,
     line_numbers: bool,
+    model: &TokenizerModel,
+    languages: &LanguageOverrides,
+    template: Option<&Template>,
 ) -> Result<()> {
+    let language = languages.detect(path, Some(content));
     match format {
-        OutputFormat::Cxml => print_as_xml(writer, path, content, line_numbers),
-        OutputFormat::Markdown => print_as_markdown(writer, path, content, line_numbers),
-        OutputFormat::Default => print_default(writer, path, content, line_numbers),
+        OutputFormat::Cxml => print_as_xml(
+            writer,
+            path,
+            content,
+            language,
+            history,
+            line_numbers,
+            model,
+        ),
+        OutputFormat::Markdown => {
+            print_as_markdown(writer, path, content, language, history, line_numbers)
+        }
+        OutputFormat::Default => print_default(writer, path, content, history, line_numbers),
+        OutputFormat::Json => print_as_json(
+            writer,
+            path,
+            content,
+            language,
+            history,
+            line_numbers,
+            model,
+        ),
+        OutputFormat::Jsonl => print_as_jsonl(
+            writer,
+            path,
+            content,
+            language,
+            history,
+            line_numbers,
+            model,
+        ),
+        OutputFormat::Template => {
+            let template =
+                template.ok_or_else(|| anyhow!("The template format requires --template"))?;
+            print_with_template(
+                writer,
+                path,
+                content,
+                language,
+                history,
+                line_numbers,
+                model,
+                template,
+            )
+        }
     }
 }
 
@@ -64,8 +104,12 @@ pub fn print_default(
     writer: &mut Writer,
     path: &Path,
     content: &str,
+    history: Option<FileHistory>,
     line_numbers: bool,
 ) -> Result<()> {
+    if let Some(history) = history {
+        writer.write(&history.summary_line())?;
+    }
     writer.write(&path.to_string_lossy())?;
     writer.write("---")?;
 
@@ -81,14 +125,58 @@ pub fn print_default(
     Ok(())
 }
 
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Wraps text in CDATA, splitting any `]]>` across two sections so it can't end the block early
+pub fn wrap_cdata(text: &str) -> String {
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
+
 pub fn print_as_xml(
     writer: &mut Writer,
     path: &Path,
     content: &str,
+    language: Option<&str>,
+    history: Option<FileHistory>,
     line_numbers: bool,
+    model: &TokenizerModel,
 ) -> Result<()> {
-    writer.write(&format!(r#"<document index="{}">"#, writer.document_index))?;
-    writer.write(&format!(r#"<source>{}</source>"#, path.to_string_lossy()))?;
+    let mut attributes = format!(r#"index="{}""#, writer.document_index);
+    if let Some(language) = language {
+        attributes.push_str(&format!(r#" language="{}""#, escape_xml(language)));
+    }
+    attributes.push_str(&format!(
+        r#" bytes="{}" tokens="{}""#,
+        content.len(),
+        tokenizers::count_tokens(content, model)
+    ));
+    if let Some(history) = history {
+        attributes.push_str(&format!(
+            r#" last_modified="{}" commits="{}" authors="{}""#,
+            history.last_modified_date(),
+            history.commits,
+            history.authors
+        ));
+    }
+
+    writer.write(&format!("<document {}>", attributes))?;
+    writer.write(&format!(
+        "<source>{}</source>",
+        escape_xml(&path.to_string_lossy())
+    ))?;
     writer.write("<document_content>")?;
 
     let content_to_write = if line_numbers {
@@ -97,7 +185,7 @@ pub fn print_as_xml(
         content.to_string()
     };
 
-    writer.write(&content_to_write)?;
+    writer.write(&wrap_cdata(&content_to_write))?;
     writer.write("</document_content>")?;
     writer.write("</document>")?;
 
@@ -109,11 +197,11 @@ pub fn print_as_markdown(
     writer: &mut Writer,
     path: &Path,
     content: &str,
+    language: Option<&str>,
+    history: Option<FileHistory>,
     line_numbers: bool,
 ) -> Result<()> {
-    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-
-    let lang = EXT_TO_LANG.get(extension).copied().unwrap_or("");
+    let lang = language.unwrap_or("");
 
     // Figure out how many backticks to use
     let mut backticks = "```".to_string();
@@ -121,6 +209,9 @@ pub fn print_as_markdown(
         backticks.push('`');
     }
 
+    if let Some(history) = history {
+        writer.write(&history.summary_line())?;
+    }
     writer.write(&path.to_string_lossy())?;
     writer.write(&format!("{}{}", backticks, lang))?;
 
@@ -134,3 +225,133 @@ pub fn print_as_markdown(
     writer.write(&backticks)?;
     Ok(())
 }
+
+fn json_document(
+    path: &Path,
+    content: &str,
+    language: Option<&str>,
+    line_numbers: bool,
+    model: &TokenizerModel,
+    history: Option<FileHistory>,
+) -> Result<String> {
+    let content_to_write = if line_numbers {
+        add_line_numbers(content)
+    } else {
+        content.to_string()
+    };
+
+    let document = JsonDocument {
+        path: path.to_string_lossy().to_string(),
+        language,
+        bytes: content.len(),
+        tokens: tokenizers::count_tokens(content, model),
+        history,
+        content: &content_to_write,
+    };
+
+    Ok(serde_json::to_string(&document)?)
+}
+
+pub fn print_as_json(
+    writer: &mut Writer,
+    path: &Path,
+    content: &str,
+    language: Option<&str>,
+    history: Option<FileHistory>,
+    line_numbers: bool,
+    model: &TokenizerModel,
+) -> Result<()> {
+    let document = json_document(path, content, language, line_numbers, model, history)?;
+
+    // The surrounding array brackets are written by the caller
+    if writer.document_index > 1 {
+        writer.write_raw(",\n")?;
+    } else {
+        writer.write_raw("\n")?;
+    }
+    writer.write_raw(&document)?;
+
+    writer.document_index += 1;
+    Ok(())
+}
+
+pub fn print_as_jsonl(
+    writer: &mut Writer,
+    path: &Path,
+    content: &str,
+    language: Option<&str>,
+    history: Option<FileHistory>,
+    line_numbers: bool,
+    model: &TokenizerModel,
+) -> Result<()> {
+    let document = json_document(path, content, language, line_numbers, model, history)?;
+    writer.write(&document)?;
+
+    writer.document_index += 1;
+    Ok(())
+}
+
+// The path relative to the current directory when it lies below it, otherwise unchanged
+pub fn relative_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+    let relative = relative.strip_prefix(".").unwrap_or(relative);
+    relative.to_string_lossy().to_string()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn print_with_template(
+    writer: &mut Writer,
+    path: &Path,
+    content: &str,
+    language: Option<&str>,
+    history: Option<FileHistory>,
+    line_numbers: bool,
+    model: &TokenizerModel,
+    template: &Template,
+) -> Result<()> {
+    let content_to_write = if line_numbers {
+        add_line_numbers(content)
+    } else {
+        content.to_string()
+    };
+
+    // Token counting is the expensive part, so skip it unless the template asks for it
+    let token_count = if template.uses("token_count") {
+        tokenizers::count_tokens(content, model).to_string()
+    } else {
+        String::new()
+    };
+
+    let mut values = HashMap::from([
+        ("path", path.to_string_lossy().to_string()),
+        ("relative_path", relative_path(path)),
+        ("language", language.unwrap_or("").to_string()),
+        ("content", content_to_write),
+        ("line_count", content.lines().count().to_string()),
+        ("token_count", token_count),
+        ("bytes", content.len().to_string()),
+        ("index", writer.document_index.to_string()),
+    ]);
+    values.extend([
+        (
+            "last_modified",
+            history.map_or(String::new(), |h| h.last_modified_date()),
+        ),
+        (
+            "commits",
+            history.map_or(String::new(), |h| h.commits.to_string()),
+        ),
+        (
+            "authors",
+            history.map_or(String::new(), |h| h.authors.to_string()),
+        ),
+    ]);
+
+    writer.write(&render_template(&template.file, &values))?;
+
+    writer.document_index += 1;
+    Ok(())
+}
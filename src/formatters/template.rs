@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+// Placeholders available in the per-file section
+pub const FILE_PLACEHOLDERS: &[&str] = &[
+    "path",
+    "relative_path",
+    "language",
+    "content",
+    "line_count",
+    "token_count",
+    "bytes",
+    "index",
+    "last_modified",
+    "commits",
+    "authors",
+];
+
+// Placeholders available in the header and footer sections
+pub const WRAPPER_PLACEHOLDERS: &[&str] = &["file_count"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub header: Option<String>,
+    pub file: String,
+    pub footer: Option<String>,
+}
+
+// Returns the text of a `{{#name}}...{{/name}}` block, minus the line breaks next to its tags
+fn extract_block(source: &str, name: &str) -> Result<Option<String>> {
+    let open = format!("{{{{#{}}}}}", name);
+    let close = format!("{{{{/{}}}}}", name);
+
+    let Some(start) = source.find(&open) else {
+        return Ok(None);
+    };
+    let body_start = start + open.len();
+    let body_end = body_start
+        + source[body_start..]
+            .find(&close)
+            .ok_or_else(|| anyhow!("Template block {} is missing {}", open, close))?;
+
+    let body = &source[body_start..body_end];
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    let body = body.strip_suffix('\n').unwrap_or(body);
+    Ok(Some(body.to_string()))
+}
+
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + 2 + len].trim());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    names
+}
+
+fn check_placeholders(text: &str, allowed: &[&str], section: &str) -> Result<()> {
+    for name in placeholders(text) {
+        if !allowed.contains(&name) {
+            return Err(anyhow!(
+                "Unknown placeholder {{{{{}}}}} in template {} section (available: {})",
+                name,
+                section,
+                allowed.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Template {
+    // A template without any blocks is used as the per-file layout
+    pub fn parse(source: &str) -> Result<Self> {
+        let header = extract_block(source, "header")?;
+        let footer = extract_block(source, "footer")?;
+        let file = match extract_block(source, "file")? {
+            Some(file) => file,
+            None if header.is_none() && footer.is_none() => {
+                source.strip_suffix('\n').unwrap_or(source).to_string()
+            }
+            None => return Err(anyhow!("Template has no {{{{#file}}}} block")),
+        };
+
+        check_placeholders(&file, FILE_PLACEHOLDERS, "file")?;
+        for (section, text) in [("header", &header), ("footer", &footer)] {
+            if let Some(text) = text {
+                check_placeholders(text, WRAPPER_PLACEHOLDERS, section)?;
+            }
+        }
+
+        Ok(Self {
+            header,
+            file,
+            footer,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read template {}", path.display()))?;
+        Self::parse(&source).with_context(|| format!("Invalid template {}", path.display()))
+    }
+
+    pub fn uses(&self, placeholder: &str) -> bool {
+        placeholders(&self.file).contains(&placeholder)
+    }
+}
+
+pub fn render_template(text: &str, values: &HashMap<&str, String>) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+
+    // Single pass, so placeholder-like text inside substituted content is left alone
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(len) => {
+                let name = after[..len].trim();
+                match values.get(name) {
+                    Some(value) => rendered.push_str(value),
+                    None => rendered.push_str(&rest[start..start + 2 + len + 2]),
+                }
+                rest = &after[len + 2..];
+            }
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use thousands::Separable;
+
+use super::{wrap_cdata, Writer};
+use crate::models::{FileStats, OutputFormat};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntry {
+    pub path: PathBuf,
+    pub stats: Option<FileStats>,
+}
+
+#[derive(Default)]
+struct TreeNode {
+    children: BTreeMap<String, TreeNode>,
+    is_file: bool,
+    stats: Option<FileStats>,
+}
+
+impl TreeNode {
+    fn insert(&mut self, entry: &TreeEntry) {
+        let mut node = self;
+        for component in entry.path.components() {
+            let name = component.as_os_str().to_string_lossy().to_string();
+            node = node.children.entry(name).or_default();
+            if let Some(stats) = entry.stats {
+                // Directories carry the rollup of everything below them
+                let rollup = node.stats.get_or_insert_with(FileStats::default);
+                rollup.tokens += stats.tokens;
+                rollup.bytes += stats.bytes;
+                rollup.lines += stats.lines;
+            }
+        }
+        node.is_file = true;
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn label(name: &str, node: &TreeNode) -> String {
+    let mut label = name.to_string();
+    if !node.is_file && !label.ends_with('/') {
+        label.push('/');
+    }
+    if let Some(stats) = node.stats {
+        label.push_str(&format!(
+            " ({} tokens, {})",
+            stats.tokens.separate_with_commas(),
+            format_size(stats.bytes)
+        ));
+    }
+    label
+}
+
+fn render_children(node: &TreeNode, prefix: &str, lines: &mut Vec<String>) {
+    let count = node.children.len();
+    for (i, (name, child)) in node.children.iter().enumerate() {
+        let last = i + 1 == count;
+        let connector = if last { "└── " } else { "├── " };
+        lines.push(format!("{}{}{}", prefix, connector, label(name, child)));
+
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        render_children(child, &child_prefix, lines);
+    }
+}
+
+pub fn render_tree(entries: &[TreeEntry]) -> String {
+    let mut root = TreeNode::default();
+    for entry in entries {
+        root.insert(entry);
+    }
+
+    // Top-level paths are printed flush left, like `tree` does for its arguments
+    let mut lines = Vec::new();
+    for (name, node) in &root.children {
+        lines.push(label(name, node));
+        render_children(node, "", &mut lines);
+    }
+    lines.join("\n")
+}
+
+pub fn print_tree(writer: &mut Writer, entries: &[TreeEntry], format: &OutputFormat) -> Result<()> {
+    let tree = render_tree(entries);
+
+    match format {
+        OutputFormat::Default => {
+            writer.write(&tree)?;
+            writer.write("")?;
+        }
+        OutputFormat::Markdown => {
+            writer.write("```text")?;
+            writer.write(&tree)?;
+            writer.write("```")?;
+            writer.write("")?;
+        }
+        OutputFormat::Cxml => {
+            writer.write("<tree>")?;
+            writer.write(&wrap_cdata(&tree))?;
+            writer.write("</tree>")?;
+        }
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Template => {
+            eprintln!(
+                "Warning: --tree is not supported for {:?} output and was skipped",
+                format
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -1,35 +1,116 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
 use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 
-pub struct Writer {
-    pub file: Option<File>,
+use super::Clipboard;
+use crate::models::Compression;
+
+// Where output ends up; compressed sinks must be finished to write their trailer
+pub enum Sink<'a> {
+    Plain(Box<dyn Write + 'a>),
+    Gzip(GzEncoder<File>),
+    Zstd(zstd::Encoder<'static, File>),
+    Clipboard(Box<Clipboard>),
+}
+
+impl Sink<'static> {
+    pub fn stdout() -> Self {
+        Sink::Plain(Box::new(io::stdout().lock()))
+    }
+
+    // Compresses when the extension asks for it, e.g. bundle.txt.gz or bundle.txt.zst
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file {}", path.display()))?;
+
+        Ok(match Compression::from_path(path) {
+            Compression::None => Sink::Plain(Box::new(file)),
+            Compression::Gzip => Sink::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+            Compression::Zstd => Sink::Zstd(zstd::Encoder::new(file, 0)?),
+        })
+    }
+}
+
+impl Sink<'_> {
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Sink::Plain(mut inner) => inner.flush(),
+            Sink::Gzip(encoder) => encoder.finish()?.flush(),
+            Sink::Zstd(encoder) => encoder.finish()?.flush(),
+            Sink::Clipboard(clipboard) => clipboard.finish(),
+        }
+    }
+}
+
+impl Write for Sink<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Plain(inner) => inner.write(buf),
+            Sink::Gzip(encoder) => encoder.write(buf),
+            Sink::Zstd(encoder) => encoder.write(buf),
+            Sink::Clipboard(clipboard) => clipboard.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(inner) => inner.flush(),
+            Sink::Gzip(encoder) => encoder.flush(),
+            Sink::Zstd(encoder) => encoder.flush(),
+            Sink::Clipboard(clipboard) => clipboard.flush(),
+        }
+    }
+}
+
+// Buffers output into one or more sinks, so concatenated output streams straight to stdout,
+// (compressed) files, the clipboard or an in-memory buffer instead of being assembled first
+pub struct Writer<'a> {
+    sinks: Vec<BufWriter<Sink<'a>>>,
     pub document_index: usize,
 }
 
-impl Writer {
-    pub fn new(path: Option<PathBuf>) -> Result<Self> {
-        let file = match path {
-            Some(p) => Some(File::create(p)?),
-            None => None,
-        };
-        Ok(Self {
-            file,
+impl<'a> Writer<'a> {
+    pub fn new(sink: impl Write + 'a) -> Self {
+        Self::from_sinks(vec![Sink::Plain(Box::new(sink))])
+    }
+
+    // Every sink receives the same output, e.g. a file and stdout for --tee
+    pub fn from_sinks(sinks: Vec<Sink<'a>>) -> Self {
+        Self {
+            sinks: sinks.into_iter().map(BufWriter::new).collect(),
             document_index: 1,
-        })
+        }
+    }
+
+    pub fn stdout() -> Writer<'static> {
+        Writer::from_sinks(vec![Sink::stdout()])
+    }
+
+    pub fn create(path: &Path) -> Result<Writer<'static>> {
+        Ok(Writer::from_sinks(vec![Sink::create(path)?]))
+    }
+
+    // Writes `content` without a trailing newline
+    pub fn write_raw(&mut self, content: &str) -> Result<()> {
+        for sink in &mut self.sinks {
+            sink.write_all(content.as_bytes())?;
+        }
+        Ok(())
     }
 
     pub fn write(&mut self, content: &str) -> Result<()> {
-        match &mut self.file {
-            Some(f) => {
-                writeln!(f, "{}", content)?;
-                Ok(())
-            }
-            None => {
-                println!("{}", content);
-                Ok(())
-            }
+        self.write_raw(content)?;
+        self.write_raw("\n")
+    }
+
+    // Flushes buffered output and finishes compression; dropping the writer instead may
+    // leave compressed output truncated
+    pub fn finish(self) -> Result<()> {
+        for sink in self.sinks {
+            sink.into_inner().map_err(|e| e.into_error())?.finish()?;
         }
+        Ok(())
     }
 }
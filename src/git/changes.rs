@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Context, Result};
+use git2::{Delta, DiffOptions, Repository, Tree};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::open_repositories;
+use crate::cli::Cli;
+use crate::models::GitSelection;
+
+pub fn git_selection(cli: &Cli) -> Option<GitSelection> {
+    if let Some(reference) = &cli.changed_since {
+        Some(GitSelection::ChangedSince(reference.clone()))
+    } else if cli.staged {
+        Some(GitSelection::Staged)
+    } else if cli.uncommitted {
+        Some(GitSelection::Uncommitted)
+    } else {
+        None
+    }
+}
+
+fn head_tree(repo: &Repository) -> Option<Tree<'_>> {
+    // An unborn branch has no HEAD yet, so everything counts as new
+    repo.head().ok()?.peel_to_tree().ok()
+}
+
+// The commit the current branch forked from `reference`, or the ref itself without a HEAD
+fn fork_point<'r>(repo: &'r Repository, reference: &str) -> Result<Tree<'r>> {
+    let target = repo
+        .revparse_single(reference)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Unknown git revision '{}'", reference))?;
+
+    let base = match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(head) => {
+            let base = repo
+                .merge_base(head.id(), target.id())
+                .with_context(|| format!("'{}' has no common history with HEAD", reference))?;
+            repo.find_commit(base)?
+        }
+        Err(_) => target,
+    };
+
+    Ok(base.tree()?)
+}
+
+// Paths, relative to the repository root, of every file added or modified by the selection
+pub fn changed_files(repo: &Repository, selection: &GitSelection) -> Result<Vec<PathBuf>> {
+    let mut options = DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+
+    let diff = match selection {
+        GitSelection::ChangedSince(reference) => {
+            let base = fork_point(repo, reference)?;
+            repo.diff_tree_to_workdir_with_index(Some(&base), Some(&mut options))?
+        }
+        GitSelection::Staged => {
+            repo.diff_tree_to_index(head_tree(repo).as_ref(), None, Some(&mut options))?
+        }
+        GitSelection::Uncommitted => {
+            repo.diff_tree_to_workdir_with_index(head_tree(repo).as_ref(), Some(&mut options))?
+        }
+    };
+
+    // Deleted files have nothing left to include
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() != Delta::Deleted)
+        .filter_map(|delta| delta.new_file().path().map(Path::to_path_buf))
+        .collect())
+}
+
+// Keeps the discovered files that the selection reports as changed, preserving their order.
+// Each input path is looked up in its own repository
+pub fn filter_changed(
+    files: Vec<PathBuf>,
+    paths: &[PathBuf],
+    selection: &GitSelection,
+) -> Result<Vec<PathBuf>> {
+    let mut changed = HashSet::new();
+    for repo in open_repositories(paths.iter().map(PathBuf::as_path))? {
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| anyhow!("Cannot select changed files in a bare repository"))?;
+        changed.extend(
+            changed_files(&repo, selection)?
+                .iter()
+                .filter_map(|path| workdir.join(path).canonicalize().ok()),
+        );
+    }
+
+    Ok(files
+        .into_iter()
+        .filter(|file| {
+            file.canonicalize()
+                .is_ok_and(|canonical| changed.contains(&canonical))
+        })
+        .collect())
+}
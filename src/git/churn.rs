@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Result};
+use git2::Repository;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::history::commits_at;
+use super::{open_repositories, repo_relative};
+use crate::models::FileHistory;
+
+// Commit count, authors and last change of every file touched in the history of `revision`,
+// from the same commit walk as --changelog. Renames are not followed, and merges are skipped
+// since they repeat their parents' changes
+pub fn file_histories(repo: &Repository, revision: &str) -> Result<HashMap<PathBuf, FileHistory>> {
+    let mut files: HashMap<PathBuf, (FileHistory, HashSet<String>)> = HashMap::new();
+    for commit in commits_at(repo, revision)? {
+        let author = match commit.email.is_empty() {
+            true => commit.author.to_lowercase(),
+            false => commit.email.to_lowercase(),
+        };
+
+        for change in commit.files {
+            let (history, authors) = files.entry(change.path).or_insert_with(|| {
+                let history = FileHistory {
+                    last_modified: commit.time,
+                    commits: 0,
+                    authors: 0,
+                };
+                (history, HashSet::new())
+            });
+            history.commits += 1;
+            history.last_modified = history.last_modified.max(commit.time);
+            authors.insert(author.clone());
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(path, (mut history, authors))| {
+            history.authors = authors.len();
+            (path, history)
+        })
+        .collect())
+}
+
+pub struct FileHistories {
+    cwd: PathBuf,
+    // Working tree root and file histories of each repository the inputs are in
+    repos: Vec<(PathBuf, HashMap<PathBuf, FileHistory>)>,
+}
+
+impl FileHistories {
+    pub fn collect<'p>(starts: impl IntoIterator<Item = &'p Path>, revision: &str) -> Result<Self> {
+        let mut repos = Vec::new();
+        for repo in open_repositories(starts)? {
+            let root = repo
+                .workdir()
+                .ok_or_else(|| anyhow!("Git metadata needs a repository with a working tree"))?
+                .canonicalize()?;
+            repos.push((root, file_histories(&repo, revision)?));
+        }
+
+        Ok(Self {
+            repos,
+            cwd: std::env::current_dir()?.canonicalize()?,
+        })
+    }
+
+    // Looks up a discovered path, relative to the current directory or absolute, in the
+    // innermost repository containing it
+    pub fn get(&self, path: &Path) -> Option<FileHistory> {
+        self.repos
+            .iter()
+            .filter_map(|(root, files)| Some((root, files, repo_relative(root, &self.cwd, path)?)))
+            .max_by_key(|(root, _, _)| root.components().count())
+            .and_then(|(_, files, relative)| files.get(&relative).copied())
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{anyhow, Context, Result};
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Oid, Patch, Repository, Tree};
+use std::path::{Path, PathBuf};
+
+// `FROM`, `FROM..TO` or `FROM...TO` as in git; without TO the working tree is compared
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevisionRange {
+    pub from: String,
+    pub to: Option<String>,
+    // `...` compares TO against its merge base with FROM
+    pub merge_base: bool,
+}
+
+impl RevisionRange {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (from, to, merge_base) = if let Some((from, to)) = spec.split_once("...") {
+            (from, Some(to), true)
+        } else if let Some((from, to)) = spec.split_once("..") {
+            (from, Some(to), false)
+        } else {
+            (spec, None, false)
+        };
+
+        if from.is_empty() {
+            return Err(anyhow!("Invalid revision range '{}': missing start", spec));
+        }
+
+        Ok(Self {
+            from: from.to_string(),
+            to: to.filter(|to| !to.is_empty()).map(str::to_string),
+            merge_base,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    // The new path, or the old one for deletions
+    pub path: PathBuf,
+    pub old_path: Option<PathBuf>,
+    pub status: Delta,
+    pub patch: String,
+}
+
+pub(super) fn resolve(repo: &Repository, revision: &str) -> Result<Oid> {
+    Ok(repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Unknown git revision '{}'", revision))?
+        .id())
+}
+
+fn tree_at<'r>(repo: &'r Repository, oid: Oid) -> Result<Tree<'r>> {
+    Ok(repo.find_commit(oid)?.tree()?)
+}
+
+fn range_diff<'r>(
+    repo: &'r Repository,
+    range: &RevisionRange,
+    options: &mut DiffOptions,
+) -> Result<Diff<'r>> {
+    let to = match &range.to {
+        Some(to) => Some(resolve(repo, to)?),
+        None => None,
+    };
+
+    let mut from = resolve(repo, &range.from)?;
+    if range.merge_base {
+        let tip = match to {
+            Some(to) => to,
+            None => resolve(repo, "HEAD")?,
+        };
+        from = repo.merge_base(from, tip)?;
+    }
+
+    let from_tree = tree_at(repo, from)?;
+    let diff = match to {
+        Some(to) => {
+            repo.diff_tree_to_tree(Some(&from_tree), Some(&tree_at(repo, to)?), Some(options))?
+        }
+        None => repo.diff_tree_to_workdir_with_index(Some(&from_tree), Some(options))?,
+    };
+    Ok(diff)
+}
+
+// One unified diff per changed file, with renames detected
+pub fn diff_range(
+    repo: &Repository,
+    range: &RevisionRange,
+    context_lines: u32,
+) -> Result<Vec<FilePatch>> {
+    let mut options = DiffOptions::new();
+    options
+        .context_lines(context_lines)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+
+    let mut diff = range_diff(repo, range, &mut options)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let mut patches = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let Some(mut patch) = Patch::from_diff(&diff, index)? else {
+            continue;
+        };
+        let delta = patch.delta();
+        let status = delta.status();
+        let old_path = delta.old_file().path().map(Path::to_path_buf);
+        let new_path = delta.new_file().path().map(Path::to_path_buf);
+
+        let Some(path) = (if status == Delta::Deleted {
+            old_path.clone()
+        } else {
+            new_path
+        }) else {
+            continue;
+        };
+
+        let text = patch.to_buf()?;
+        patches.push(FilePatch {
+            path,
+            old_path: old_path.filter(|_| status == Delta::Renamed),
+            status,
+            patch: String::from_utf8_lossy(&text).to_string(),
+        });
+    }
+
+    Ok(patches)
+}
+
+// Content of `path` at the end of the range: the TO revision or the working tree
+pub fn content_after(
+    repo: &Repository,
+    range: &RevisionRange,
+    path: &Path,
+) -> Result<Option<String>> {
+    let Some(to) = &range.to else {
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| anyhow!("Cannot read the working tree of a bare repository"))?;
+        return Ok(std::fs::read_to_string(workdir.join(path)).ok());
+    };
+
+    let tree = tree_at(repo, resolve(repo, to)?)?;
+    let Ok(entry) = tree.get_path(path) else {
+        return Ok(None);
+    };
+    let blob = entry.to_object(repo)?.peel_to_blob()?;
+    Ok(std::str::from_utf8(blob.content()).ok().map(str::to_string))
+}
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use git2::{DiffOptions, Patch, Repository, Revwalk, Sort};
+use std::path::PathBuf;
+
+use super::diff::resolve;
+use super::RevisionRange;
+use crate::utils::format_date;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitInfo {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    // Seconds since the epoch and the author's UTC offset in minutes
+    pub time: i64,
+    pub offset_minutes: i32,
+    pub message: String,
+    // Against the first parent; empty for merges
+    pub files: Vec<FileChange>,
+    pub diff: Option<String>,
+}
+
+impl CommitInfo {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(10)]
+    }
+
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+
+    // Author date as YYYY-MM-DD in the author's timezone
+    pub fn date(&self) -> String {
+        format_date(self.time + self.offset_minutes as i64 * 60)
+    }
+
+    // Like `git log --stat`, followed by the diff when it was collected
+    pub fn render(&self, with_diff: bool) -> String {
+        let mut text = format!(
+            "commit {}\nAuthor: {} <{}>\nDate:   {}\n\n",
+            self.id,
+            self.author,
+            self.email,
+            self.date()
+        );
+        for line in self.message.trim_end().lines() {
+            if !line.is_empty() {
+                text.push_str("    ");
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        if !self.files.is_empty() {
+            text.push('\n');
+            let (mut insertions, mut deletions) = (0, 0);
+            for file in &self.files {
+                text.push_str(&format!(
+                    " {} | +{} -{}\n",
+                    file.path.display(),
+                    file.insertions,
+                    file.deletions
+                ));
+                insertions += file.insertions;
+                deletions += file.deletions;
+            }
+            text.push_str(&format!(
+                " {} files changed, {} insertions(+), {} deletions(-)\n",
+                self.files.len(),
+                insertions,
+                deletions
+            ));
+        }
+
+        if let (true, Some(diff)) = (with_diff, &self.diff) {
+            text.push('\n');
+            text.push_str(diff);
+        }
+        text
+    }
+}
+
+// Commits reachable from TO (or HEAD) but not from FROM, oldest first
+pub fn commit_history(
+    repo: &Repository,
+    range: &RevisionRange,
+    with_diffs: bool,
+) -> Result<Vec<CommitInfo>> {
+    let mut walk = repo.revwalk()?;
+    walk.push(resolve(repo, range.to.as_deref().unwrap_or("HEAD"))?)?;
+    walk.hide(resolve(repo, &range.from)?)?;
+    walk_commits(repo, walk, with_diffs)
+}
+
+// Every commit reachable from `revision`, oldest first, without diffs
+pub fn commits_at(repo: &Repository, revision: &str) -> Result<Vec<CommitInfo>> {
+    let mut walk = repo.revwalk()?;
+    walk.push(resolve(repo, revision)?)?;
+    walk_commits(repo, walk, false)
+}
+
+// Reads the commits of `walk` with the files each changed. Both --changelog and the per-file
+// histories of --git-metadata and --priority go through here
+fn walk_commits(repo: &Repository, mut walk: Revwalk, with_diffs: bool) -> Result<Vec<CommitInfo>> {
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+    let mut commits = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let author = commit.author();
+        let mut info = CommitInfo {
+            id: commit.id().to_string(),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            time: author.when().seconds(),
+            offset_minutes: author.when().offset_minutes(),
+            message: String::from_utf8_lossy(commit.message_bytes()).to_string(),
+            files: Vec::new(),
+            diff: None,
+        };
+
+        // Merges only repeat the changes of the commits they bring in
+        if commit.parent_count() <= 1 {
+            let parent_tree = match commit.parent_count() {
+                0 => None,
+                _ => Some(commit.parent(0)?.tree()?),
+            };
+            let diff = repo.diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&commit.tree()?),
+                Some(&mut DiffOptions::new()),
+            )?;
+
+            let mut text = String::new();
+            for index in 0..diff.deltas().len() {
+                let Some(mut patch) = Patch::from_diff(&diff, index)? else {
+                    continue;
+                };
+                let delta = patch.delta();
+                let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                    continue;
+                };
+                let (_, insertions, deletions) = patch.line_stats()?;
+                info.files.push(FileChange {
+                    path: path.to_path_buf(),
+                    insertions,
+                    deletions,
+                });
+                if with_diffs {
+                    text.push_str(&String::from_utf8_lossy(&patch.to_buf()?));
+                }
+            }
+            if with_diffs {
+                info.diff = Some(text);
+            }
+        }
+
+        commits.push(info);
+    }
+
+    Ok(commits)
+}
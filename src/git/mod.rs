@@ -0,0 +1,69 @@
+mod changes;
+mod churn;
+mod diff;
+mod history;
+mod revision;
+
+pub use changes::{changed_files, filter_changed, git_selection};
+pub use churn::{file_histories, FileHistories};
+pub use diff::{content_after, diff_range, FilePatch, RevisionRange};
+pub use history::{commit_history, CommitInfo, FileChange};
+pub use revision::{Snapshot, SnapshotOptions};
+
+use anyhow::{anyhow, Result};
+use git2::Repository;
+use std::path::{Path, PathBuf};
+
+use crate::utils::normalize_path;
+
+// Opens the repository containing `path`, searching parent directories like git does
+pub fn open_repository(path: &Path) -> Result<Repository> {
+    let start = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
+    };
+    let start = if start.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        start
+    };
+
+    Repository::discover(start).map_err(|e| {
+        anyhow!(
+            "{} is not inside a git repository: {}",
+            path.display(),
+            e.message()
+        )
+    })
+}
+
+// The distinct repositories containing `starts`, in order, or the current directory's if
+// there are none
+pub(crate) fn open_repositories<'p>(
+    starts: impl IntoIterator<Item = &'p Path>,
+) -> Result<Vec<Repository>> {
+    let mut repos: Vec<Repository> = Vec::new();
+    for start in starts {
+        let repo = open_repository(start)?;
+        let git_dir = repo.path().canonicalize().ok();
+        if !repos
+            .iter()
+            .any(|known| known.path().canonicalize().ok() == git_dir)
+        {
+            repos.push(repo);
+        }
+    }
+    if repos.is_empty() {
+        repos.push(open_repository(Path::new("."))?);
+    }
+    Ok(repos)
+}
+
+// `path` relative to the repository root `root`, whether or not it exists in the working tree
+pub(crate) fn repo_relative(root: &Path, cwd: &Path, path: &Path) -> Option<PathBuf> {
+    normalize_path(path, cwd)
+        .strip_prefix(root)
+        .ok()
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,273 @@
+use anyhow::{anyhow, Result};
+use git2::{Index, ObjectType, Oid, Repository, Tree};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::diff::resolve;
+use super::{open_repositories, open_repository, repo_relative};
+
+const SYMLINK_MODE: i32 = 0o120000;
+
+// The files of one commit, or of the index, addressed by the paths they would have in the
+// working tree
+pub struct Snapshot {
+    cwd: PathBuf,
+    time: SystemTime,
+    repos: Vec<SnapshotRepository>,
+}
+
+struct SnapshotRepository {
+    repo: Mutex<Repository>,
+    root: PathBuf,
+    entries: BTreeMap<PathBuf, Oid>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotOptions {
+    pub gitignore: bool,
+    pub hidden: bool,
+}
+
+fn workdir(repo: &Repository, option: &str) -> Result<PathBuf> {
+    Ok(repo
+        .workdir()
+        .ok_or_else(|| anyhow!("{} needs a repository with a working tree", option))?
+        .canonicalize()?)
+}
+
+impl Snapshot {
+    pub fn open(start: &Path, revision: &str, options: SnapshotOptions) -> Result<Self> {
+        let repo = open_repository(start)?;
+        let root = workdir(&repo, "--rev")?;
+        let commit = repo.find_commit(resolve(&repo, revision)?)?;
+        let time = UNIX_EPOCH + Duration::from_secs(commit.time().seconds().max(0) as u64);
+
+        let mut entries = BTreeMap::new();
+        collect_entries(
+            &repo,
+            &commit.tree()?,
+            Path::new(""),
+            &mut Vec::new(),
+            options,
+            &mut entries,
+        )?;
+        drop(commit);
+
+        Ok(Self {
+            cwd: std::env::current_dir()?.canonicalize()?,
+            time,
+            repos: vec![SnapshotRepository {
+                repo: Mutex::new(repo),
+                root,
+                entries,
+            }],
+        })
+    }
+
+    // The staged content of every repository the inputs are in, for --staged
+    pub fn from_index<'p>(
+        starts: impl IntoIterator<Item = &'p Path>,
+        options: SnapshotOptions,
+    ) -> Result<Self> {
+        let mut repos = Vec::new();
+        let mut time = UNIX_EPOCH;
+        for repo in open_repositories(starts)? {
+            let root = workdir(&repo, "--staged")?;
+            let index = repo.index()?;
+            if let Ok(modified) = std::fs::metadata(repo.path().join("index"))?.modified() {
+                time = time.max(modified);
+            }
+
+            let entries = index_entries(&repo, &index, options)?;
+            drop(index);
+            repos.push(SnapshotRepository {
+                repo: Mutex::new(repo),
+                root,
+                entries,
+            });
+        }
+
+        Ok(Self {
+            cwd: std::env::current_dir()?.canonicalize()?,
+            time,
+            repos,
+        })
+    }
+
+    // The innermost repository containing `path`, and the path relative to its root
+    fn locate(&self, path: &Path) -> Option<(&SnapshotRepository, PathBuf)> {
+        self.repos
+            .iter()
+            .filter_map(|repo| Some((repo, repo_relative(&repo.root, &self.cwd, path)?)))
+            .max_by_key(|(repo, _)| repo.root.components().count())
+    }
+
+    // Files at or below `path`, named the way a working tree walk from `path` names them
+    pub fn files_under(&self, path: &Path) -> Vec<PathBuf> {
+        let Some((repo, prefix)) = self.locate(path) else {
+            return Vec::new();
+        };
+        if repo.entries.contains_key(&prefix) {
+            return vec![path.to_path_buf()];
+        }
+
+        repo.entries
+            .keys()
+            .filter_map(|entry| entry.strip_prefix(&prefix).ok())
+            .filter(|rest| !rest.as_os_str().is_empty())
+            .map(|rest| path.join(rest))
+            .collect()
+    }
+
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let (repo, oid) = self
+            .locate(path)
+            .and_then(|(repo, relative)| Some((repo, *repo.entries.get(&relative)?)))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not in revision"))?;
+        let repo = repo.repo.lock().unwrap_or_else(|e| e.into_inner());
+        let blob = repo.find_blob(oid).map_err(io::Error::other)?;
+        Ok(blob.content().to_vec())
+    }
+
+    // The commit time, or when the index was last written
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+}
+
+// Whether the innermost .gitignore with an opinion ignores `path`
+fn is_ignored(matchers: &[Gitignore], path: &Path, is_dir: bool) -> bool {
+    for matcher in matchers.iter().rev() {
+        match matcher.matched(path, is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    false
+}
+
+fn collect_entries(
+    repo: &Repository,
+    tree: &Tree,
+    dir: &Path,
+    matchers: &mut Vec<Gitignore>,
+    options: SnapshotOptions,
+    entries: &mut BTreeMap<PathBuf, Oid>,
+) -> Result<()> {
+    // The .gitignore committed in this directory applies to everything below it
+    let gitignore = match tree.get_name(".gitignore") {
+        Some(entry) if options.gitignore => Some(repo.find_blob(entry.id())?),
+        _ => None,
+    };
+    if let Some(blob) = &gitignore {
+        let mut builder = GitignoreBuilder::new(dir);
+        for line in String::from_utf8_lossy(blob.content()).lines() {
+            // Invalid patterns are skipped, as git does
+            let _ = builder.add_line(None, line);
+        }
+        matchers.push(builder.build()?);
+    }
+
+    for entry in tree.iter() {
+        let Some(name) = entry.name() else {
+            continue;
+        };
+        if !options.hidden && name.starts_with('.') {
+            continue;
+        }
+
+        let path = dir.join(name);
+        let is_dir = entry.kind() == Some(ObjectType::Tree);
+        if is_ignored(matchers, &path, is_dir) {
+            continue;
+        }
+
+        match entry.kind() {
+            Some(ObjectType::Tree) => {
+                let subtree = repo.find_tree(entry.id())?;
+                collect_entries(repo, &subtree, &path, matchers, options, entries)?;
+            }
+            Some(ObjectType::Blob) if entry.filemode() != SYMLINK_MODE => {
+                entries.insert(path, entry.id());
+            }
+            // Symlinks and submodules have no content of their own at this revision
+            _ => {}
+        }
+    }
+
+    if gitignore.is_some() {
+        matchers.pop();
+    }
+    Ok(())
+}
+
+// The staged files, filtered like collect_entries filters a commit's tree: each directory is
+// checked against the .gitignore files staged above it, then the file against those and its own
+fn index_entries(
+    repo: &Repository,
+    index: &Index,
+    options: SnapshotOptions,
+) -> Result<BTreeMap<PathBuf, Oid>> {
+    let staged: Vec<(PathBuf, Oid, u32)> = index
+        .iter()
+        // Entries of an unresolved conflict have a non-zero stage
+        .filter(|entry| (entry.flags >> 12) & 0x3 == 0)
+        .map(|entry| {
+            let path = PathBuf::from(String::from_utf8_lossy(&entry.path).as_ref());
+            (path, entry.id, entry.mode)
+        })
+        .collect();
+
+    let mut gitignores = BTreeMap::new();
+    if options.gitignore {
+        for (path, oid, _) in &staged {
+            if path.file_name().is_some_and(|name| name == ".gitignore") {
+                let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+                let mut builder = GitignoreBuilder::new(&dir);
+                for line in String::from_utf8_lossy(repo.find_blob(*oid)?.content()).lines() {
+                    // Invalid patterns are skipped, as git does
+                    let _ = builder.add_line(None, line);
+                }
+                gitignores.insert(dir, builder.build()?);
+            }
+        }
+    }
+
+    let mut entries = BTreeMap::new();
+    for (path, oid, mode) in staged {
+        // Symlinks and submodules have no content of their own in the index
+        if mode & 0o170000 != 0o100000 {
+            continue;
+        }
+        let hidden = path
+            .iter()
+            .any(|component| component.to_string_lossy().starts_with('.'));
+        if !options.hidden && hidden {
+            continue;
+        }
+
+        let mut matchers = Vec::new();
+        let mut ignored = false;
+        let mut dir = PathBuf::new();
+        for component in path.iter() {
+            if let Some(gitignore) = gitignores.get(&dir) {
+                matchers.push(gitignore.clone());
+            }
+            dir.push(component);
+            if is_ignored(&matchers, &dir, dir != path) {
+                ignored = true;
+                break;
+            }
+        }
+        if !ignored {
+            entries.insert(path, oid);
+        }
+    }
+    Ok(entries)
+}
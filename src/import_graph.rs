@@ -0,0 +1,375 @@
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::session::Session;
+use crate::syntax_tree::{imports, Import};
+use crate::utils::normalize_path;
+
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+// TypeScript imports name the compiled file, so `./a.js` may be `./a.ts` in the source
+const COMPILED_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("js", &["ts", "tsx"]),
+    ("jsx", &["tsx"]),
+    ("mjs", &["mts"]),
+    ("cjs", &["cts"]),
+];
+
+// Which of the selected files import which, following Rust `mod` and `use`, Python `import`,
+// JavaScript/TypeScript `import`, `export ... from` and `require`, and Go imports. Only files
+// among the selected ones count; everything else is treated as an external dependency
+pub struct ImportGraph<'s> {
+    session: &'s Session,
+    cwd: PathBuf,
+    // Normalized path to the path as discovered, in discovery order
+    files: HashMap<PathBuf, PathBuf>,
+    order: Vec<PathBuf>,
+    // Non-test Go files by directory, which is what a Go package is
+    go_packages: HashMap<PathBuf, Vec<PathBuf>>,
+    // Crate names from Cargo.toml and module paths from go.mod, by directory
+    crate_names: HashMap<PathBuf, Option<String>>,
+    go_modules: HashMap<PathBuf, Option<String>>,
+}
+
+impl<'s> ImportGraph<'s> {
+    pub fn new(files: &[PathBuf], session: &'s Session) -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let mut graph = Self {
+            files: HashMap::new(),
+            order: Vec::new(),
+            go_packages: HashMap::new(),
+            crate_names: HashMap::new(),
+            go_modules: HashMap::new(),
+            session,
+            cwd,
+        };
+        for file in files {
+            let normalized = normalize_path(file, &graph.cwd);
+            let name = normalized.to_string_lossy();
+            if name.ends_with(".go") && !name.ends_with("_test.go") {
+                if let Some(dir) = normalized.parent() {
+                    graph
+                        .go_packages
+                        .entry(dir.to_path_buf())
+                        .or_default()
+                        .push(normalized.clone());
+                }
+            }
+            graph.order.push(normalized.clone());
+            graph.files.insert(normalized, file.clone());
+        }
+        Ok(graph)
+    }
+
+    // The files reachable from `entries` through at most `depth` imports, in discovery order
+    pub fn reachable(&mut self, entries: &[PathBuf], depth: Option<usize>) -> Result<Vec<PathBuf>> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        for entry in entries {
+            let normalized = normalize_path(entry, &self.cwd);
+            if !self.files.contains_key(&normalized) {
+                return Err(anyhow!(
+                    "The entry point {} is not among the selected files",
+                    entry.display()
+                ));
+            }
+            if visited.insert(normalized.clone()) {
+                queue.push_back((normalized, 0));
+            }
+        }
+
+        while let Some((file, level)) = queue.pop_front() {
+            if depth.is_some_and(|depth| level >= depth) {
+                continue;
+            }
+            for dependency in self.dependencies(&file) {
+                if visited.insert(dependency.clone()) {
+                    queue.push_back((dependency, level + 1));
+                }
+            }
+        }
+
+        Ok(self
+            .order
+            .iter()
+            .filter(|file| visited.contains(*file))
+            .map(|file| self.files[file].clone())
+            .collect())
+    }
+
+    // The selected files that `file` imports directly
+    pub fn dependencies(&mut self, file: &Path) -> Vec<PathBuf> {
+        let Some(original) = self.files.get(file) else {
+            return Vec::new();
+        };
+        let Ok(content) = self.session.read_to_string(original) else {
+            return Vec::new();
+        };
+        let Some(language) = self.session.languages.detect(original, Some(&content)) else {
+            return Vec::new();
+        };
+
+        let mut dependencies: Vec<PathBuf> = imports(&content, language)
+            .into_iter()
+            .flat_map(|import| self.resolve(file, language, import))
+            .collect();
+
+        // The other files of a Go package are always part of it
+        if language == "go" {
+            if let Some(package) = file.parent().and_then(|dir| self.go_packages.get(dir)) {
+                dependencies.extend(package.iter().filter(|other| *other != file).cloned());
+            }
+        }
+
+        let mut seen = HashSet::new();
+        dependencies.retain(|dependency| dependency != file && seen.insert(dependency.clone()));
+        dependencies
+    }
+
+    fn resolve(&mut self, file: &Path, language: &str, import: Import) -> Vec<PathBuf> {
+        match import {
+            Import::RustModule { parents, name } => {
+                let mut dir = rust_module_dir(file);
+                dir.extend(parents);
+                self.rust_module(&dir, &name).into_iter().collect()
+            }
+            Import::RustUse { parents, path } => self.resolve_rust_use(file, &parents, &path),
+            Import::Python {
+                level,
+                module,
+                names,
+            } => self.resolve_python(file, level, &module, &names),
+            Import::Path(path) if language == "go" => self.resolve_go(file, &path),
+            Import::Path(specifier) => self.resolve_script(file, &specifier).into_iter().collect(),
+        }
+    }
+
+    fn existing(&self, path: PathBuf) -> Option<PathBuf> {
+        self.files.contains_key(&path).then_some(path)
+    }
+
+    // `name.rs` or `name/mod.rs` in `dir`
+    fn rust_module(&self, dir: &Path, name: &str) -> Option<PathBuf> {
+        self.existing(dir.join(format!("{}.rs", name)))
+            .or_else(|| self.existing(dir.join(name).join("mod.rs")))
+    }
+
+    // The file of the longest module prefix of `path` below `dir`: `a::b::Item` is in a/b.rs
+    // when that exists, otherwise in a.rs
+    fn rust_path(&self, dir: &Path, path: &[String]) -> Option<PathBuf> {
+        (1..=path.len()).rev().find_map(|len| {
+            let (name, parents) = path[..len].split_last()?;
+            let mut dir = dir.to_path_buf();
+            dir.extend(parents);
+            self.rust_module(&dir, name)
+        })
+    }
+
+    fn resolve_rust_use(
+        &mut self,
+        file: &Path,
+        parents: &[String],
+        path: &[String],
+    ) -> Vec<PathBuf> {
+        let mut module_dir = rust_module_dir(file);
+        module_dir.extend(parents);
+        let root = self.crate_root(file);
+        let Some(first) = path.first() else {
+            return Vec::new();
+        };
+
+        match first.as_str() {
+            "crate" => self.rust_path(&root, &path[1..]).into_iter().collect(),
+            "self" => self
+                .rust_path(&module_dir, &path[1..])
+                .into_iter()
+                .collect(),
+            "super" => {
+                let supers = path
+                    .iter()
+                    .take_while(|segment| *segment == "super")
+                    .count();
+                let mut dir = module_dir;
+                for _ in 0..supers {
+                    dir.pop();
+                }
+                self.rust_path(&dir, &path[supers..]).into_iter().collect()
+            }
+            // A binary using its own library crate by name
+            name if self.crate_name(&root).as_deref() == Some(name) => {
+                let library = self.existing(root.join("lib.rs"));
+                library
+                    .into_iter()
+                    .chain(self.rust_path(&root, &path[1..]))
+                    .collect()
+            }
+            // A child module in scope, or else an external crate
+            _ => self.rust_path(&module_dir, path).into_iter().collect(),
+        }
+    }
+
+    // The nearest directory above `file` holding lib.rs or main.rs, or called src
+    fn crate_root(&self, file: &Path) -> PathBuf {
+        file.ancestors()
+            .skip(1)
+            .find(|dir| {
+                self.files.contains_key(&dir.join("lib.rs"))
+                    || self.files.contains_key(&dir.join("main.rs"))
+                    || dir.file_name().is_some_and(|name| name == "src")
+            })
+            .or(file.parent())
+            .unwrap_or(Path::new(""))
+            .to_path_buf()
+    }
+
+    // The package name in the Cargo.toml next to the crate root, as code refers to it
+    fn crate_name(&mut self, root: &Path) -> Option<String> {
+        let manifest_dir = root.parent()?.to_path_buf();
+        self.crate_names
+            .entry(manifest_dir.clone())
+            .or_insert_with(|| {
+                let manifest = self
+                    .session
+                    .read_to_string(&manifest_dir.join("Cargo.toml"))
+                    .ok()?;
+                let mut in_package = false;
+                for line in manifest.lines().map(str::trim) {
+                    if line.starts_with('[') {
+                        in_package = line == "[package]";
+                    } else if let Some(value) = line.strip_prefix("name").filter(|_| in_package) {
+                        let name = value
+                            .trim_start()
+                            .strip_prefix('=')?
+                            .trim()
+                            .trim_matches('"');
+                        return Some(name.replace('-', "_"));
+                    }
+                }
+                None
+            })
+            .clone()
+    }
+
+    fn resolve_python(
+        &self,
+        file: &Path,
+        level: usize,
+        module: &[String],
+        names: &[String],
+    ) -> Vec<PathBuf> {
+        let Some(dir) = file.parent() else {
+            return Vec::new();
+        };
+        // Relative imports start from the file's package, absolute ones from any directory
+        // above it that has the module, nearest first
+        let bases: Vec<&Path> = if level > 0 {
+            dir.ancestors().nth(level - 1).into_iter().collect()
+        } else {
+            dir.ancestors().collect()
+        };
+
+        for base in bases {
+            // `from a import b` imports the module a.b if there is one, else a name from a
+            let submodules: Vec<PathBuf> = names
+                .iter()
+                .filter_map(|name| {
+                    let mut path = module.to_vec();
+                    path.push(name.clone());
+                    self.python_module(base, &path)
+                })
+                .collect();
+            if !submodules.is_empty() {
+                return submodules;
+            }
+            if let Some(found) = self.python_module(base, module) {
+                return vec![found];
+            }
+        }
+        Vec::new()
+    }
+
+    // `a/b.py` or `a/b/__init__.py` for the module a.b
+    fn python_module(&self, base: &Path, module: &[String]) -> Option<PathBuf> {
+        let mut path = base.to_path_buf();
+        path.extend(module);
+        if module.is_empty() {
+            return self.existing(path.join("__init__.py"));
+        }
+        self.existing(path.with_extension("py"))
+            .or_else(|| self.existing(path.join("__init__.py")))
+    }
+
+    // Relative specifiers only; packages come from node_modules, which isn't followed
+    fn resolve_script(&self, file: &Path, specifier: &str) -> Option<PathBuf> {
+        if !specifier.starts_with('.') {
+            return None;
+        }
+        let base = normalize_path(&file.parent()?.join(specifier), &self.cwd);
+        let extension = base.extension().and_then(|extension| extension.to_str());
+
+        let mut candidates = vec![base.clone()];
+        if let Some((_, sources)) = COMPILED_EXTENSIONS
+            .iter()
+            .find(|(compiled, _)| Some(*compiled) == extension)
+        {
+            candidates.extend(sources.iter().map(|source| base.with_extension(source)));
+        }
+        let name = base.to_string_lossy();
+        candidates.extend(
+            SCRIPT_EXTENSIONS
+                .iter()
+                .map(|extension| PathBuf::from(format!("{}.{}", name, extension))),
+        );
+        candidates.extend(
+            SCRIPT_EXTENSIONS
+                .iter()
+                .map(|extension| base.join(format!("index.{}", extension))),
+        );
+        candidates
+            .into_iter()
+            .find(|candidate| self.files.contains_key(candidate))
+    }
+
+    // Imports within the file's Go module, from the module path in the nearest go.mod
+    fn resolve_go(&mut self, file: &Path, import: &str) -> Vec<PathBuf> {
+        let Some((root, module)) = file
+            .ancestors()
+            .skip(1)
+            .find_map(|dir| Some((dir.to_path_buf(), self.go_module(dir)?)))
+        else {
+            return Vec::new();
+        };
+        let Some(rest) = import.strip_prefix(module.as_str()) else {
+            return Vec::new();
+        };
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return Vec::new();
+        }
+        let package = root.join(rest.trim_start_matches('/'));
+        self.go_packages.get(&package).cloned().unwrap_or_default()
+    }
+
+    fn go_module(&mut self, dir: &Path) -> Option<String> {
+        self.go_modules
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let manifest = self.session.read_to_string(&dir.join("go.mod")).ok()?;
+                manifest.lines().find_map(|line| {
+                    let module = line.trim().strip_prefix("module")?;
+                    Some(module.trim().trim_matches('"').to_string())
+                })
+            })
+            .clone()
+    }
+}
+
+// Where the modules a Rust file declares live: next to main.rs, lib.rs and mod.rs, and in a
+// directory named after any other file
+fn rust_module_dir(file: &Path) -> PathBuf {
+    let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    match file.file_stem().and_then(|stem| stem.to_str()) {
+        Some("main" | "lib" | "mod") | None => dir,
+        Some(stem) => dir.join(stem),
+    }
+}
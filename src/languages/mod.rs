@@ -0,0 +1,224 @@
+use anyhow::{anyhow, Context, Result};
+use globset::{Glob, GlobMatcher};
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+mod table;
+
+use table::{ALIASES, EXTENSIONS, FILENAMES, FILENAME_PREFIXES, INTERPRETERS};
+
+// Modelines are only looked for this many lines from the start and end of a file
+const MODELINE_SCAN_LINES: usize = 5;
+
+lazy_static! {
+    static ref EXT_TO_LANG: HashMap<&'static str, &'static str> =
+        EXTENSIONS.iter().copied().collect();
+    static ref FILENAME_TO_LANG: HashMap<&'static str, &'static str> =
+        FILENAMES.iter().copied().collect();
+    static ref INTERPRETER_TO_LANG: HashMap<&'static str, &'static str> =
+        INTERPRETERS.iter().copied().collect();
+    // Every language name we know, plus aliases, for normalizing modeline values
+    static ref KNOWN_NAMES: HashMap<&'static str, &'static str> = {
+        let mut m = HashMap::new();
+        for (_, language) in EXTENSIONS.iter().chain(FILENAMES).chain(INTERPRETERS) {
+            m.insert(*language, *language);
+        }
+        for (extension, language) in EXTENSIONS {
+            m.entry(*extension).or_insert(*language);
+        }
+        for (alias, language) in ALIASES {
+            m.insert(*alias, *language);
+        }
+        m
+    };
+}
+
+pub struct LanguageOverride {
+    pattern: String,
+    matcher: GlobMatcher,
+    language: String,
+}
+
+impl LanguageOverride {
+    // Parses `PATTERN=LANGUAGE`, e.g. `*.tpl=jinja` or `Jenkinsfile.*=groovy`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (pattern, language) = spec.split_once('=').ok_or_else(|| {
+            anyhow!(
+                "Invalid language override '{}': expected PATTERN=LANGUAGE",
+                spec
+            )
+        })?;
+        Self::new(pattern.trim(), language.trim())
+    }
+
+    pub fn new(pattern: &str, language: &str) -> Result<Self> {
+        if pattern.is_empty() || language.is_empty() {
+            return Err(anyhow!(
+                "Invalid language override '{}={}': pattern and language are required",
+                pattern,
+                language
+            ));
+        }
+        let matcher = Glob::new(pattern)
+            .with_context(|| format!("Invalid language override pattern '{}'", pattern))?
+            .compile_matcher();
+        Ok(Self {
+            pattern: pattern.to_string(),
+            matcher,
+            language: language.to_string(),
+        })
+    }
+
+    // Patterns containing a slash match the whole path, others just the file name
+    pub fn matches(&self, path: &Path) -> bool {
+        if self.pattern.contains('/') {
+            return self.matcher.is_match(path);
+        }
+        path.file_name()
+            .is_some_and(|name| self.matcher.is_match(Path::new(name)))
+    }
+}
+
+fn user_languages_path() -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("summarize").join("languages.json"))
+}
+
+fn load_user_overrides(path: &Path) -> Result<Vec<LanguageOverride>> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read language map {}", path.display()))?;
+    let entries: BTreeMap<String, String> = serde_json::from_str(&json)
+        .map_err(|e| anyhow!("Invalid language map {}: {}", path.display(), e))?;
+    entries
+        .iter()
+        .map(|(pattern, language)| LanguageOverride::new(pattern, language))
+        .collect()
+}
+
+// The --language rules and the user's config-dir languages.json, checked before detect_language
+#[derive(Default)]
+pub struct LanguageOverrides {
+    rules: Vec<LanguageOverride>,
+}
+
+impl LanguageOverrides {
+    pub fn new(rules: Vec<LanguageOverride>) -> Self {
+        Self { rules }
+    }
+
+    // Command-line overrides come first so they win over the user's config-dir languages.json
+    pub fn load(specs: &[String]) -> Result<Self> {
+        let mut rules = specs
+            .iter()
+            .map(|spec| LanguageOverride::parse(spec))
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(path) = user_languages_path().filter(|path| path.exists()) {
+            rules.extend(load_user_overrides(&path)?);
+        }
+
+        Ok(Self { rules })
+    }
+
+    // The first matching override, or else what detect_language finds
+    pub fn detect(&self, path: &Path, content: Option<&str>) -> Option<&str> {
+        match self.rules.iter().find(|rule| rule.matches(path)) {
+            Some(rule) => Some(rule.language.as_str()),
+            None => detect_language(path, content),
+        }
+    }
+}
+
+fn normalize(name: &str) -> Option<&'static str> {
+    KNOWN_NAMES.get(name.to_lowercase().as_str()).copied()
+}
+
+pub fn language_from_filename(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    if let Some(language) = FILENAME_TO_LANG.get(name) {
+        return Some(language);
+    }
+    FILENAME_PREFIXES
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, language)| *language)
+}
+
+pub fn language_from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    EXT_TO_LANG.get(extension.as_str()).copied()
+}
+
+// `#!/usr/bin/python3`, `#!/usr/bin/env bash` and `#!/usr/bin/env -S node --flag`
+pub fn language_from_shebang(content: &str) -> Option<&'static str> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+
+    let mut interpreter = Path::new(words.next()?).file_name()?.to_str()?;
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+
+    if let Some(language) = INTERPRETER_TO_LANG.get(interpreter) {
+        return Some(language);
+    }
+    // Versioned interpreters such as python3.12 or ruby2.7
+    let unversioned = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETER_TO_LANG.get(unversioned).copied()
+}
+
+fn vim_modeline(line: &str) -> Option<&str> {
+    // The marker must start a word, so `index: ft=...` isn't taken for an `ex:` modeline
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(i, _)| *i == 0 || line[..*i].ends_with(char::is_whitespace))
+            .map(|(i, _)| i + marker.len())
+    })?;
+    let rest = &line[start..];
+    let value_start = ["filetype=", "ft=", "syntax=", "syn="]
+        .iter()
+        .find_map(|key| rest.find(key).map(|i| i + key.len()))?;
+    let value = &rest[value_start..];
+    let end = value
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '+' || c == '-'))
+        .unwrap_or(value.len());
+    Some(&value[..end])
+}
+
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let start = line.find("-*-")? + 3;
+    let end = start + line[start..].find("-*-")?;
+    let variables = line[start..end].trim();
+
+    if !variables.contains(':') {
+        return Some(variables);
+    }
+    variables.split(';').find_map(|variable| {
+        let (key, value) = variable.split_once(':')?;
+        (key.trim().eq_ignore_ascii_case("mode")).then(|| value.trim())
+    })
+}
+
+// Vim (`vim: set ft=python:`) and Emacs (`-*- mode: ruby -*-`) modelines
+pub fn language_from_modeline(content: &str) -> Option<&'static str> {
+    let lines: Vec<&str> = content.lines().collect();
+    let head = lines.iter().take(MODELINE_SCAN_LINES);
+    let tail = lines
+        .iter()
+        .skip(MODELINE_SCAN_LINES.max(lines.len().saturating_sub(MODELINE_SCAN_LINES)));
+
+    head.chain(tail)
+        .find_map(|line| vim_modeline(line).or_else(|| emacs_modeline(line)))
+        .and_then(normalize)
+}
+
+// Modelines win, since they state the language explicitly; then well-known file names,
+// extensions and finally the shebang line for extensionless scripts. Overrides are applied on
+// top by LanguageOverrides::detect
+pub fn detect_language(path: &Path, content: Option<&str>) -> Option<&'static str> {
+    content
+        .and_then(language_from_modeline)
+        .or_else(|| language_from_filename(path))
+        .or_else(|| language_from_extension(path))
+        .or_else(|| content.and_then(language_from_shebang))
+}
@@ -0,0 +1,302 @@
+// Extension -> language, using the identifiers Markdown renderers expect for fences
+pub const EXTENSIONS: &[(&str, &str)] = &[
+    // Systems languages
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("c++", "cpp"),
+    ("hh", "cpp"),
+    ("hpp", "cpp"),
+    ("hxx", "cpp"),
+    ("ino", "cpp"),
+    ("m", "objectivec"),
+    ("mm", "objectivec"),
+    ("rs", "rust"),
+    ("go", "go"),
+    ("zig", "zig"),
+    ("nim", "nim"),
+    ("d", "d"),
+    ("v", "v"),
+    ("odin", "odin"),
+    ("cr", "crystal"),
+    ("asm", "asm"),
+    ("s", "asm"),
+    ("f", "fortran"),
+    ("f90", "fortran"),
+    ("f95", "fortran"),
+    ("ada", "ada"),
+    ("adb", "ada"),
+    ("ads", "ada"),
+    ("pas", "pascal"),
+    ("cob", "cobol"),
+    ("cbl", "cobol"),
+    // JVM and .NET
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("sc", "scala"),
+    ("groovy", "groovy"),
+    ("gradle", "groovy"),
+    ("clj", "clojure"),
+    ("cljs", "clojure"),
+    ("cljc", "clojure"),
+    ("edn", "clojure"),
+    ("cs", "csharp"),
+    ("csx", "csharp"),
+    ("fs", "fsharp"),
+    ("fsx", "fsharp"),
+    ("vb", "vbnet"),
+    // Scripting languages
+    ("py", "python"),
+    ("pyi", "python"),
+    ("pyw", "python"),
+    ("pyx", "cython"),
+    ("rb", "ruby"),
+    ("rake", "ruby"),
+    ("gemspec", "ruby"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("pm", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("jl", "julia"),
+    ("tcl", "tcl"),
+    ("awk", "awk"),
+    ("sed", "sed"),
+    // Shells
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("ksh", "bash"),
+    ("ps1", "powershell"),
+    ("psm1", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    // Functional languages
+    ("hs", "haskell"),
+    ("lhs", "haskell"),
+    ("ml", "ocaml"),
+    ("mli", "ocaml"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hrl", "erlang"),
+    ("elm", "elm"),
+    ("purs", "purescript"),
+    ("rkt", "racket"),
+    ("scm", "scheme"),
+    ("lisp", "lisp"),
+    ("el", "elisp"),
+    ("gleam", "gleam"),
+    // Web
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("tsx", "tsx"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("astro", "astro"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("xhtml", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("sass", "sass"),
+    ("less", "less"),
+    ("styl", "stylus"),
+    ("hbs", "handlebars"),
+    ("ejs", "ejs"),
+    ("erb", "erb"),
+    ("jinja", "jinja"),
+    ("j2", "jinja"),
+    ("liquid", "liquid"),
+    ("twig", "twig"),
+    ("pug", "pug"),
+    ("wasm", "wasm"),
+    ("wat", "wasm"),
+    // Mobile
+    ("swift", "swift"),
+    ("dart", "dart"),
+    // Data and configuration
+    ("json", "json"),
+    ("jsonc", "jsonc"),
+    ("json5", "json5"),
+    ("ipynb", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("ini", "ini"),
+    ("cfg", "ini"),
+    ("conf", "ini"),
+    ("properties", "properties"),
+    ("env", "dotenv"),
+    ("xml", "xml"),
+    ("xsd", "xml"),
+    ("xsl", "xml"),
+    ("svg", "xml"),
+    ("plist", "xml"),
+    ("csproj", "xml"),
+    ("csv", "csv"),
+    ("tsv", "tsv"),
+    ("sql", "sql"),
+    ("graphql", "graphql"),
+    ("gql", "graphql"),
+    ("proto", "protobuf"),
+    ("thrift", "thrift"),
+    ("avsc", "json"),
+    ("prisma", "prisma"),
+    // Infrastructure
+    ("tf", "hcl"),
+    ("tfvars", "hcl"),
+    ("hcl", "hcl"),
+    ("nix", "nix"),
+    ("dockerfile", "dockerfile"),
+    ("cmake", "cmake"),
+    ("mk", "makefile"),
+    ("mak", "makefile"),
+    ("bzl", "starlark"),
+    ("star", "starlark"),
+    ("jsonnet", "jsonnet"),
+    ("rego", "rego"),
+    ("cue", "cue"),
+    // Hardware and scientific
+    ("sv", "systemverilog"),
+    ("svh", "systemverilog"),
+    ("vhd", "vhdl"),
+    ("vhdl", "vhdl"),
+    ("sol", "solidity"),
+    ("move", "move"),
+    ("cu", "cuda"),
+    ("cuh", "cuda"),
+    ("glsl", "glsl"),
+    ("hlsl", "hlsl"),
+    ("wgsl", "wgsl"),
+    ("mat", "matlab"),
+    // Documentation
+    ("md", "markdown"),
+    ("markdown", "markdown"),
+    ("mdx", "mdx"),
+    ("rst", "rst"),
+    ("adoc", "asciidoc"),
+    ("tex", "latex"),
+    ("bib", "bibtex"),
+    ("org", "org"),
+    // Patches and editor files
+    ("diff", "diff"),
+    ("patch", "diff"),
+    ("vim", "vim"),
+];
+
+// Exact file name -> language, for files that have no useful extension
+pub const FILENAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "dockerfile"),
+    ("Containerfile", "dockerfile"),
+    ("Makefile", "makefile"),
+    ("makefile", "makefile"),
+    ("GNUmakefile", "makefile"),
+    ("CMakeLists.txt", "cmake"),
+    ("Rakefile", "ruby"),
+    ("Gemfile", "ruby"),
+    ("Guardfile", "ruby"),
+    ("Podfile", "ruby"),
+    ("Vagrantfile", "ruby"),
+    ("Brewfile", "ruby"),
+    ("Jenkinsfile", "groovy"),
+    ("Justfile", "just"),
+    ("justfile", "just"),
+    ("BUILD", "starlark"),
+    ("BUILD.bazel", "starlark"),
+    ("WORKSPACE", "starlark"),
+    ("Tiltfile", "starlark"),
+    ("Pipfile", "toml"),
+    ("Cargo.lock", "toml"),
+    ("go.mod", "go.mod"),
+    (".bashrc", "bash"),
+    (".bash_profile", "bash"),
+    (".profile", "bash"),
+    (".zshrc", "zsh"),
+    (".vimrc", "vim"),
+    (".gitignore", "gitignore"),
+    (".dockerignore", "gitignore"),
+    (".gitattributes", "gitattributes"),
+    (".editorconfig", "ini"),
+    (".env", "dotenv"),
+    ("nginx.conf", "nginx"),
+];
+
+// File name prefixes, such as Dockerfile.dev or Makefile.inc
+pub const FILENAME_PREFIXES: &[(&str, &str)] = &[
+    ("Dockerfile.", "dockerfile"),
+    ("Containerfile.", "dockerfile"),
+    ("Makefile.", "makefile"),
+    (".env.", "dotenv"),
+];
+
+// Interpreter named on a shebang line -> language
+pub const INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("dash", "bash"),
+    ("ash", "bash"),
+    ("ksh", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("python", "python"),
+    ("python2", "python"),
+    ("python3", "python"),
+    ("pypy", "python"),
+    ("pypy3", "python"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("deno", "typescript"),
+    ("bun", "javascript"),
+    ("ts-node", "typescript"),
+    ("lua", "lua"),
+    ("Rscript", "r"),
+    ("julia", "julia"),
+    ("awk", "awk"),
+    ("gawk", "awk"),
+    ("tclsh", "tcl"),
+    ("pwsh", "powershell"),
+    ("elixir", "elixir"),
+    ("escript", "erlang"),
+    ("runhaskell", "haskell"),
+    ("scala", "scala"),
+    ("groovy", "groovy"),
+    ("make", "makefile"),
+];
+
+// Alternative names used in modelines, normalized to the names above
+pub const ALIASES: &[(&str, &str)] = &[
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("shell-script", "bash"),
+    ("py", "python"),
+    ("python3", "python"),
+    ("js", "javascript"),
+    ("js2", "javascript"),
+    ("ts", "typescript"),
+    ("c++", "cpp"),
+    ("objc", "objectivec"),
+    ("cs", "csharp"),
+    ("yml", "yaml"),
+    ("make", "makefile"),
+    ("emacs-lisp", "elisp"),
+    ("tex", "latex"),
+    ("conf", "ini"),
+    ("dosini", "ini"),
+    ("ps1", "powershell"),
+    ("terraform", "hcl"),
+    ("proto", "protobuf"),
+];
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use comfy_table::{ContentArrangement, Table};
 use ignore::WalkBuilder;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -7,37 +7,137 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-// Directly use tempfile::NamedTempFile instead of importing the crate
 use thousands::Separable;
 
+pub mod archive;
+pub mod changelog;
 pub mod cli;
+pub mod extractors;
 pub mod formatters;
+pub mod git;
+pub mod import_graph;
+pub mod languages;
 pub mod llm;
 pub mod models;
+pub mod parsers;
+pub mod session;
+pub mod syntax_tree;
 pub mod tokenizers;
+pub mod transforms;
+pub mod unpack;
 pub mod utils;
 
-use crate::formatters::{print_path, Writer};
-use crate::models::{OutputFormat, TokenReport};
-use crate::utils::should_ignore;
+use crate::formatters::{
+    print_path, print_tree, render_template, Clipboard, Sink, Template, TreeEntry, Writer,
+};
+use crate::import_graph::ImportGraph;
+use crate::models::{
+    ContextFit, CostEstimate, FileStats, OutputFormat, ReportFormat, TokenBudget, TokenDiff,
+    TokenReport,
+};
+use crate::session::Session;
+use crate::utils::{normalize_path, should_ignore, PathRenderer};
+
+pub fn display_token_report(report: &TokenReport, cli: &cli::Cli, session: &Session) -> Result<()> {
+    let rendered = match cli.report_format {
+        ReportFormat::Table => {
+            print_token_table(report, cli, session);
+            return Ok(());
+        }
+        ReportFormat::Json => report.to_json()? + "\n",
+        ReportFormat::Csv => report.to_csv(),
+    };
+
+    // Machine-readable reports go to the output file when given, stdout otherwise
+    match &cli.output_file {
+        Some(output_file) => {
+            let mut writer = Writer::create(output_file)?;
+            writer.write_raw(&rendered)?;
+            writer.finish()?;
+            eprintln!("Token report written to {}", output_file.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+fn print_language_table(report: &TokenReport) {
+    let mut languages: Vec<_> = report.languages.iter().collect();
+    languages.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.tokens));
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Language", "Tokens", "Lines", "Share"]);
+
+    for (language, stats) in languages {
+        let share = if report.total_tokens > 0 {
+            stats.tokens as f64 / report.total_tokens as f64 * 100.0
+        } else {
+            0.0
+        };
+        table.add_row(vec![
+            language.clone(),
+            stats.tokens.separate_with_commas(),
+            stats.lines.separate_with_commas(),
+            format!("{:.1}%", share),
+        ]);
+    }
 
-pub fn display_token_report(report: &TokenReport, cli: &cli::Cli) -> Result<()> {
+    println!("{table}");
+}
+
+// The options that made the counted content smaller than the files
+fn saved_by(cli: &cli::Cli) -> String {
+    let options: Vec<&str> = [
+        (!cli.extractors.is_empty(), "--extract"),
+        (cli.outline, "--outline"),
+        (!cli.transforms.is_empty(), "--strip"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, option)| enabled.then_some(option))
+    .collect();
+    match options.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+// Whether transform_content can change what's read from a file
+fn rewrites_content(cli: &cli::Cli) -> bool {
+    !cli.extractors.is_empty() || cli.outline || !cli.transforms.is_empty()
+}
+
+fn print_token_table(report: &TokenReport, cli: &cli::Cli, session: &Session) {
     let model = &cli.tokenizer_model;
 
     if cli.verbose {
         let mut table = Table::new();
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(vec!["File", "Tokens"]);
-
-        // Sort entries by path for consistent output
-        let mut entries: Vec<_> = report.file_tokens.iter().collect();
-        entries.sort_by(|a, b| a.0.cmp(b.0));
+        let mut header = vec!["File", "Tokens"];
+        if cli.git_metadata {
+            header.extend(["Last modified", "Commits", "Authors"]);
+        }
+        table.set_header(header);
 
-        for (path, tokens) in entries {
-            table.add_row(vec![
+        // BTreeMap keeps entries sorted by path for consistent output
+        for (path, stats) in &report.files {
+            let mut row = vec![
                 path.to_string_lossy().to_string(),
-                tokens.separate_with_commas(),
-            ]);
+                stats.tokens.separate_with_commas(),
+            ];
+            if cli.git_metadata {
+                match report.histories.get(path) {
+                    Some(history) => row.extend([
+                        history.last_modified_date(),
+                        history.commits.to_string(),
+                        history.authors.to_string(),
+                    ]),
+                    None => row.extend(["-".to_string(), "-".to_string(), "-".to_string()]),
+                }
+            }
+            table.add_row(row);
         }
 
         // Add total row
@@ -47,6 +147,10 @@ pub fn display_token_report(report: &TokenReport, cli: &cli::Cli) -> Result<()>
         ]);
 
         println!("{table}");
+
+        if !report.languages.is_empty() {
+            print_language_table(report);
+        }
     } else {
         println!(
             "Total tokens: {}",
@@ -54,7 +158,18 @@ pub fn display_token_report(report: &TokenReport, cli: &cli::Cli) -> Result<()>
         );
     }
 
-    println!("Files processed: {}", report.file_tokens.len());
+    if report.saved_tokens > 0 {
+        let original = report.total_tokens + report.saved_tokens;
+        println!(
+            "Tokens saved by {}: {} ({:.1}% of {})",
+            saved_by(cli),
+            report.saved_tokens.separate_with_commas(),
+            report.saved_tokens as f64 * 100.0 / original as f64,
+            original.separate_with_commas()
+        );
+    }
+
+    println!("Files processed: {}", report.files.len());
 
     // Format the duration in a human-readable way
     if report.duration_ms > 0 {
@@ -84,50 +199,161 @@ pub fn display_token_report(report: &TokenReport, cli: &cli::Cli) -> Result<()>
     }
 
     if cli.show_cost {
-        let (input_cost_per_k, output_cost_per_k) =
-            tokenizers::get_token_cost(model, report.total_tokens);
-        let input_cost = (report.total_tokens as f64 / 1000.0) * input_cost_per_k;
-
-        // Assume a typical response might be about 20% of the input size for cost estimation
-        let estimated_output_tokens = (report.total_tokens as f64 * 0.2).round() as usize;
-        let output_cost = (estimated_output_tokens as f64 / 1000.0) * output_cost_per_k;
+        let cost = report.cost.clone().unwrap_or_else(|| {
+            CostEstimate::for_model(model, report.total_tokens, &session.pricing)
+        });
 
         println!("\nEstimated cost ({:?}):", model);
         println!(
             "  Input: ${:.4} ({} tokens @ ${:.4}/1K tokens)",
-            input_cost,
+            cost.input_cost,
             report.total_tokens.separate_with_commas(),
-            input_cost_per_k
+            cost.input_cost_per_1k
         );
         println!(
             "  Output: ${:.4} (est. {} tokens @ ${:.4}/1K tokens)*",
-            output_cost,
-            estimated_output_tokens.separate_with_commas(),
-            output_cost_per_k
+            cost.output_cost,
+            cost.estimated_output_tokens.separate_with_commas(),
+            cost.output_cost_per_1k
         );
-        println!("  Total: ${:.4}", input_cost + output_cost);
+        println!("  Total: ${:.4}", cost.total_cost);
+        if let Some(cached_input_cost_per_1k) = cost.cached_input_cost_per_1k {
+            println!(
+                "  Cached input: ${:.4} (@ ${:.4}/1K tokens)",
+                (report.total_tokens as f64 / 1000.0) * cached_input_cost_per_1k,
+                cached_input_cost_per_1k
+            );
+        }
+        if let Some(batch_total_cost) = cost.batch_total_cost {
+            println!("  Batch total: ${:.4}", batch_total_cost);
+        }
         println!("\n* Output tokens are estimated at 20% of input tokens");
     }
 
+    if !report.context_fit.is_empty() {
+        print_context_fit(report);
+    }
+}
+
+fn print_context_fit(report: &TokenReport) {
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Model",
+        "Tokens",
+        "Context Window",
+        "Used",
+        "Fits",
+        "Headroom",
+        "Est. Cost",
+    ]);
+
+    for fit in &report.context_fit {
+        table.add_row(vec![
+            fit.model.clone(),
+            fit.tokens.separate_with_commas(),
+            fit.context_window.separate_with_commas(),
+            format!("{:.1}%", fit.percent_used),
+            if fit.fits { "yes" } else { "no" }.to_string(),
+            fit.headroom.separate_with_commas(),
+            format!("${:.4}", fit.cost.total_cost),
+        ]);
+    }
+
+    println!("\nContext window fit:");
+    println!("{table}");
+}
+
+pub fn display_token_diff(diff: &TokenDiff) {
+    if diff.files.is_empty() {
+        println!("No token changes ({} files unchanged)", diff.unchanged);
+    } else {
+        let mut table = Table::new();
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec!["Status", "File", "Old", "New", "Delta"]);
+
+        for file in &diff.files {
+            table.add_row(vec![
+                format!("{:?}", file.status),
+                file.path.to_string_lossy().to_string(),
+                file.old_tokens.separate_with_commas(),
+                file.new_tokens.separate_with_commas(),
+                format_delta(file.delta),
+            ]);
+        }
+
+        println!("{table}");
+        println!(
+            "{} files changed, {} unchanged",
+            diff.files.len(),
+            diff.unchanged
+        );
+    }
+
+    let percent = if diff.old_total > 0 {
+        format!(
+            " ({:+.1}%)",
+            diff.delta as f64 * 100.0 / diff.old_total as f64
+        )
+    } else {
+        String::new()
+    };
+    println!(
+        "Total tokens: {} -> {} ({}){}",
+        diff.old_total.separate_with_commas(),
+        diff.new_total.separate_with_commas(),
+        format_delta(diff.delta),
+        percent
+    );
+}
+
+fn format_delta(delta: i64) -> String {
+    if delta > 0 {
+        format!("+{}", delta.separate_with_commas())
+    } else {
+        delta.separate_with_commas()
+    }
+}
+
+pub fn process_token_diff(old_report: &Path, new_report: &Path) -> Result<()> {
+    let old = TokenReport::load(old_report)?;
+    let new = TokenReport::load(new_report)?;
+
+    display_token_diff(&TokenDiff::between(&old, &new));
+
     Ok(())
 }
 
-pub fn process_path(
-    path: &Path,
-    cli: &cli::Cli,
-    writer: &mut Writer,
-    output_format: &OutputFormat,
-) -> Result<()> {
-    if path.is_file() {
-        match std::fs::read_to_string(path) {
-            Ok(content) => {
-                print_path(writer, path, &content, output_format, cli.line_numbers)?;
-            }
-            Err(_) => {
-                // Skip this file silently
-            }
+// The --ignore and --extension filters, shared by directory walks and git diffs
+pub fn matches_filters(path: &Path, cli: &cli::Cli) -> bool {
+    // Check custom ignore patterns
+    if should_ignore(path, &cli.ignore_patterns, cli.ignore_files_only) {
+        return false;
+    }
+
+    // Check extensions
+    if !cli.extensions.is_empty() {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        if !cli.extensions.iter().any(|ext| ext == extension) {
+            return false;
         }
-        return Ok(());
+    }
+
+    true
+}
+
+// Walks `path` and returns every file that passes the CLI filters, in walk order
+pub fn discover_files(path: &Path, cli: &cli::Cli, session: &Session) -> Vec<PathBuf> {
+    if let Some(snapshot) = &session.snapshot {
+        return snapshot
+            .files_under(path)
+            .into_iter()
+            .filter(|file| matches_filters(file, cli))
+            .collect();
+    }
+
+    if path.is_file() {
+        return vec![path.to_path_buf()];
     }
 
     // Process a directory using WalkBuilder, which properly handles .gitignore files
@@ -165,6 +391,7 @@ pub fn process_path(
 
     // Process the entries using the walker
     let walker = builder.build();
+    let mut files = Vec::new();
 
     for result in walker {
         let entry = match result {
@@ -183,81 +410,512 @@ pub fn process_path(
             continue;
         }
 
-        // Check custom ignore patterns
-        if should_ignore(entry_path, &cli.ignore_patterns, cli.ignore_files_only) {
+        if !matches_filters(entry_path, cli) {
             continue;
         }
 
-        // Check extensions
-        if !cli.extensions.is_empty() {
-            let extension = entry_path
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("");
+        files.push(entry_path.to_path_buf());
+    }
 
-            if !cli.extensions.iter().any(|ext| ext == extension) {
-                continue;
+    files
+}
+
+// Every file under `paths` that passes the CLI filters, narrowed to git changes and --entry
+// imports and ordered by --priority if requested
+pub fn select_files(paths: &[PathBuf], cli: &cli::Cli, session: &Session) -> Result<Vec<PathBuf>> {
+    let files: Vec<PathBuf> = paths
+        .iter()
+        .flat_map(|path| discover_files(path, cli, session))
+        .collect();
+
+    let mut files = match git::git_selection(cli) {
+        Some(selection) => {
+            let changed = git::filter_changed(files, paths, &selection)?;
+            if changed.is_empty() {
+                eprintln!("Warning: no changed files match the selection");
             }
+            changed
+        }
+        None => files,
+    };
+
+    if !cli.entries.is_empty() {
+        files = ImportGraph::new(&files, session)?.reachable(&cli.entries, cli.entry_depth)?;
+    }
+
+    // Most active first; files without history keep their order at the end
+    if let Some(priority) = cli.priority {
+        files.sort_by_cached_key(|file| {
+            std::cmp::Reverse(
+                session
+                    .file_history(file)
+                    .map(|history| priority.score(&history)),
+            )
+        });
+    }
+
+    Ok(files)
+}
+
+// File content as it goes into the output: from disk or --rev, then rewritten by --strip
+pub fn read_content(file: &Path, cli: &cli::Cli, session: &Session) -> std::io::Result<String> {
+    let content = session.read_to_string(file)?;
+    Ok(transform_content(content, file, cli, session))
+}
+
+// Applies --extract, then --outline, then --strip. Files no extractor or outline applies to
+// keep their content
+pub fn transform_content(
+    content: String,
+    file: &Path,
+    cli: &cli::Cli,
+    session: &Session,
+) -> String {
+    let content = match cli.extractors.is_empty() {
+        true => content,
+        false => extractors::extract(
+            &content,
+            file,
+            &cli.extractors,
+            &extractors::ExtractOptions::from_cli(cli),
+        )
+        .unwrap_or(content),
+    };
+    let content = match cli.outline {
+        true => session
+            .languages
+            .detect(file, Some(&content))
+            .and_then(|language| syntax_tree::outline(&content, language))
+            .unwrap_or(content),
+        false => content,
+    };
+    if cli.transforms.is_empty() {
+        return content;
+    }
+    transforms::apply_transforms(&content, file, &cli.transforms, &session.languages)
+}
+
+pub fn process_file(
+    file: &Path,
+    display_path: &Path,
+    cli: &cli::Cli,
+    session: &Session,
+    writer: &mut Writer,
+    output_format: &OutputFormat,
+    template: Option<&Template>,
+) -> Result<()> {
+    match read_content(file, cli, session) {
+        Ok(content) => {
+            let history = session.file_history(file).filter(|_| cli.git_metadata);
+            print_path(
+                writer,
+                display_path,
+                &content,
+                history,
+                output_format,
+                cli.line_numbers,
+                &cli.tokenizer_model,
+                &session.languages,
+                template,
+            )
+        }
+        Err(_) => {
+            // Skip this file but continue processing others
+            Ok(())
         }
+    }
+}
+
+pub fn process_path(
+    path: &Path,
+    cli: &cli::Cli,
+    session: &Session,
+    writer: &mut Writer,
+    output_format: &OutputFormat,
+) -> Result<()> {
+    let template = load_template(cli, output_format)?;
+    let mut renderer = PathRenderer::from_cli(cli)?;
+    for file in select_files(&[path.to_path_buf()], cli, session)? {
+        let display_path = renderer.render(&file);
+        process_file(
+            &file,
+            &display_path,
+            cli,
+            session,
+            writer,
+            output_format,
+            template.as_ref(),
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn load_template(cli: &cli::Cli, output_format: &OutputFormat) -> Result<Option<Template>> {
+    if !matches!(output_format, OutputFormat::Template) {
+        return Ok(None);
+    }
+    match &cli.template {
+        Some(path) => Ok(Some(Template::load(path)?)),
+        None => Err(anyhow!("The template format requires --template")),
+    }
+}
 
-        // Process file
-        match std::fs::read_to_string(entry_path) {
-            Ok(content) => {
-                print_path(
-                    writer,
-                    entry_path,
+// Files that can't be read as text are left out, matching what gets concatenated
+fn tree_entries(
+    files: &[PathBuf],
+    display_paths: &[PathBuf],
+    cli: &cli::Cli,
+    session: &Session,
+) -> Vec<TreeEntry> {
+    files
+        .iter()
+        .zip(display_paths)
+        .filter_map(|(file, display_path)| {
+            let content = read_content(file, cli, session).ok()?;
+            let stats = cli.tree_stats.then(|| {
+                FileStats::from_content(
                     &content,
-                    output_format,
-                    cli.line_numbers,
-                )?;
+                    tokenizers::count_tokens(&content, &cli.tokenizer_model),
+                )
+            });
+            Some(TreeEntry {
+                path: display_path.clone(),
+                stats,
+            })
+        })
+        .collect()
+}
+
+// Start XML document, JSON array or template header if needed
+pub(crate) fn write_header(
+    writer: &mut Writer,
+    output_format: &OutputFormat,
+    template: Option<&Template>,
+    file_count: usize,
+) -> Result<()> {
+    match output_format {
+        OutputFormat::Cxml => writer.write("<documents>")?,
+        OutputFormat::Json => writer.write_raw("[")?,
+        OutputFormat::Template => {
+            if let Some(header) = template.and_then(|t| t.header.as_ref()) {
+                let values = HashMap::from([("file_count", file_count.to_string())]);
+                writer.write(&render_template(header, &values))?;
             }
-            Err(_) => {
-                // Skip this file but continue processing others
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// End XML document, JSON array or template footer if needed
+pub(crate) fn write_footer(
+    writer: &mut Writer,
+    output_format: &OutputFormat,
+    template: Option<&Template>,
+    file_count: usize,
+) -> Result<()> {
+    match output_format {
+        OutputFormat::Cxml => writer.write("</documents>")?,
+        OutputFormat::Json => writer.write("\n]")?,
+        OutputFormat::Template => {
+            if let Some(footer) = template.and_then(|t| t.footer.as_ref()) {
+                let values = HashMap::from([("file_count", file_count.to_string())]);
+                writer.write(&render_template(footer, &values))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Unified diffs for `range`, one document per touched file named `<path>.diff`, then
+// optionally each file's full content after the change
+pub fn write_diff_contents(
+    range: &str,
+    cli: &cli::Cli,
+    session: &Session,
+    output_format: &OutputFormat,
+    writer: &mut Writer,
+) -> Result<()> {
+    let range = git::RevisionRange::parse(range)?;
+    let start = cli
+        .paths
+        .first()
+        .map(PathBuf::as_path)
+        .unwrap_or(Path::new("."));
+    let repo = git::open_repository(start)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("--git-diff needs a repository with a working tree"))?
+        .canonicalize()?;
+
+    // Only changes under the given paths that pass the usual filters
+    let scopes: Vec<PathBuf> = cli
+        .paths
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+    let patches: Vec<git::FilePatch> = git::diff_range(&repo, &range, cli.diff_context)?
+        .into_iter()
+        .filter(|patch| {
+            let absolute = workdir.join(&patch.path);
+            scopes.iter().any(|scope| absolute.starts_with(scope))
+                && matches_filters(&patch.path, cli)
+        })
+        .collect();
+
+    let mut contents = Vec::new();
+    if cli.diff_with_content {
+        for patch in &patches {
+            if let Some(content) = git::content_after(&repo, &range, &patch.path)? {
+                contents.push((patch.path.clone(), content));
             }
         }
     }
 
+    if patches.is_empty() {
+        eprintln!("Warning: no changes found for --git-diff");
+    }
+    if cli.tree {
+        eprintln!("Warning: --tree is not supported with --git-diff and was skipped");
+    }
+
+    let template = load_template(cli, output_format)?;
+    let mut renderer = PathRenderer::from_cli(cli)?;
+    let document_count = patches.len() + contents.len();
+    write_header(writer, output_format, template.as_ref(), document_count)?;
+
+    for patch in &patches {
+        let display_path = renderer.render(&patch.path);
+        print_path(
+            writer,
+            Path::new(&format!("{}.diff", display_path.display())),
+            &patch.patch,
+            None,
+            output_format,
+            cli.line_numbers,
+            &cli.tokenizer_model,
+            &session.languages,
+            template.as_ref(),
+        )?;
+    }
+
+    for (path, content) in &contents {
+        print_path(
+            writer,
+            &renderer.render(path),
+            content,
+            None,
+            output_format,
+            cli.line_numbers,
+            &cli.tokenizer_model,
+            &session.languages,
+            template.as_ref(),
+        )?;
+    }
+
+    write_footer(writer, output_format, template.as_ref(), document_count)?;
+
+    if let Some(path_map) = &cli.path_map {
+        renderer.write_mapping(path_map)?;
+    }
+
     Ok(())
 }
 
-pub fn collect_file_contents(
+// Writes the --symbol definitions and what they reference, one document per definition,
+// named `path:first-last` after the lines it came from
+pub fn write_symbol_contents(
     paths: &[PathBuf],
     cli: &cli::Cli,
+    session: &Session,
     output_format: &OutputFormat,
-) -> Result<String> {
-    // Create a temporary file to store the output
-    let temp_file = tempfile::NamedTempFile::new()?;
-    let temp_path = temp_file.path().to_path_buf();
+    writer: &mut Writer,
+) -> Result<()> {
+    let files: Vec<(PathBuf, String, &str)> = select_files(paths, cli, session)?
+        .into_iter()
+        .filter_map(|file| {
+            let content = session.read_to_string(&file).ok()?;
+            let language = session.languages.detect(&file, Some(&content))?;
+            Some((file, content, language))
+        })
+        .collect();
+    let index = syntax_tree::SymbolIndex::build(&files);
+    let symbols = index.select(&cli.symbols, cli.symbol_depth)?;
+
+    if cli.tree {
+        eprintln!("Warning: --tree is not supported with --symbol and was skipped");
+    }
+
+    let template = load_template(cli, output_format)?;
+    let mut renderer = PathRenderer::from_cli(cli)?;
+    write_header(writer, output_format, template.as_ref(), symbols.len())?;
 
-    // Create a writer that writes to our temp file
-    let mut writer = Writer::new(Some(temp_path.clone()))?;
+    for symbol in &symbols {
+        let display_path = format!(
+            "{}:{}-{}",
+            renderer.render(&symbol.path).display(),
+            symbol.start_line,
+            symbol.end_line
+        );
+        let text = match cli.transforms.is_empty() {
+            true => symbol.text.clone(),
+            false => transforms::apply_transforms(
+                &symbol.text,
+                &symbol.path,
+                &cli.transforms,
+                &session.languages,
+            ),
+        };
+        // Line numbers follow the file rather than the excerpt
+        let text = match cli.line_numbers {
+            true => formatters::add_line_numbers_from(&text, symbol.start_line),
+            false => text,
+        };
+        print_path(
+            writer,
+            Path::new(&display_path),
+            &text,
+            None,
+            output_format,
+            false,
+            &cli.tokenizer_model,
+            &session.languages,
+            template.as_ref(),
+        )?;
+    }
+
+    write_footer(writer, output_format, template.as_ref(), symbols.len())?;
+
+    if let Some(path_map) = &cli.path_map {
+        renderer.write_mapping(path_map)?;
+    }
+
+    Ok(())
+}
+
+// Streams the concatenated output for `paths` into `writer`
+pub fn write_file_contents(
+    paths: &[PathBuf],
+    cli: &cli::Cli,
+    session: &Session,
+    output_format: &OutputFormat,
+    writer: &mut Writer,
+) -> Result<()> {
+    if let Some(range) = &cli.git_diff {
+        return write_diff_contents(range, cli, session, output_format, writer);
+    }
+    if let Some(range) = &cli.changelog {
+        return changelog::write_history(range, cli, session, output_format, writer);
+    }
+    if !cli.symbols.is_empty() {
+        return write_symbol_contents(paths, cli, session, output_format, writer);
+    }
+
+    let template = load_template(cli, output_format)?;
+    let files = select_files(paths, cli, session)?;
+
+    // Work out every displayed path up front so the tree and the contents agree
+    let mut renderer = PathRenderer::from_cli(cli)?;
+    let display_paths: Vec<PathBuf> = files.iter().map(|file| renderer.render(file)).collect();
+
+    write_header(writer, output_format, template.as_ref(), files.len())?;
 
-    // Start XML document if needed
-    if matches!(output_format, OutputFormat::Cxml) {
-        writer.write("<documents>")?;
+    // Show the project layout before any file contents
+    if cli.tree {
+        print_tree(
+            writer,
+            &tree_entries(&files, &display_paths, cli, session),
+            output_format,
+        )?;
     }
 
-    // Process each path
-    for path in paths {
-        process_path(path, cli, &mut writer, output_format)?;
+    // Process each file
+    for (file, display_path) in files.iter().zip(&display_paths) {
+        process_file(
+            file,
+            display_path,
+            cli,
+            session,
+            writer,
+            output_format,
+            template.as_ref(),
+        )?;
     }
 
-    // End XML document if needed
-    if matches!(output_format, OutputFormat::Cxml) {
-        writer.write("</documents>")?;
+    write_footer(writer, output_format, template.as_ref(), files.len())?;
+
+    if let Some(path_map) = &cli.path_map {
+        renderer.write_mapping(path_map)?;
     }
 
-    // Read the file contents
-    let content = std::fs::read_to_string(temp_path)?;
+    Ok(())
+}
 
-    Ok(content)
+// Sinks requested on the command line: --output, --clipboard and stdout with --tee
+pub fn output_sinks(cli: &cli::Cli) -> Result<Vec<Sink<'static>>> {
+    let mut sinks = Vec::new();
+    if let Some(output_file) = &cli.output_file {
+        sinks.push(Sink::create(output_file)?);
+    }
+    if cli.clipboard {
+        sinks.push(Sink::Clipboard(Box::new(Clipboard::new()?)));
+    }
+    if cli.tee {
+        sinks.push(Sink::stdout());
+    }
+    Ok(sinks)
 }
 
-pub fn process_token_count(cli: &cli::Cli) -> Result<()> {
-    // Initialize token report
-    let mut report = TokenReport::new();
+// Collects the concatenated output in memory, for callers that need it as one string
+pub fn collect_file_contents(
+    paths: &[PathBuf],
+    cli: &cli::Cli,
+    session: &Session,
+    output_format: &OutputFormat,
+) -> Result<String> {
+    let mut buffer = Vec::new();
+    let mut writer = Writer::new(&mut buffer);
+    write_file_contents(paths, cli, session, output_format, &mut writer)?;
+    writer.finish()?;
+
+    Ok(String::from_utf8(buffer)?)
+}
+
+// Stats and detected language for each counted file
+type FileResults<'s> = HashMap<PathBuf, (FileStats, Option<&'s str>)>;
+
+pub fn process_token_count(cli: &cli::Cli, session: &Session) -> Result<()> {
+    configure_thread_pool(cli);
+    let report = count_tokens(cli, session)?;
+
+    // Display token counting results
+    display_token_report(&report, cli, session)?;
+
+    check_token_budget(&report, cli)
+}
+
+// Counts tokens at two revisions of the input paths, as --rev would, and shows the changes
+pub fn process_revision_diff(cli: &cli::Cli, old_revision: &str, new_revision: &str) -> Result<()> {
+    configure_thread_pool(cli);
+
+    let mut reports = Vec::new();
+    for revision in [old_revision, new_revision] {
+        let cli = cli::Cli {
+            rev: Some(revision.to_string()),
+            ..cli.clone()
+        };
+        let session = Session::from_cli(&cli)?;
+        reports.push(count_tokens(&cli, &session)?);
+    }
+
+    display_token_diff(&TokenDiff::between(&reports[0], &reports[1]));
+
+    Ok(())
+}
 
-    // Configure thread pool if specified
+fn configure_thread_pool(cli: &cli::Cli) {
     if cli.num_threads > 0 {
         rayon::ThreadPoolBuilder::new()
             .num_threads(cli.num_threads)
@@ -266,25 +924,54 @@ pub fn process_token_count(cli: &cli::Cli) -> Result<()> {
                 eprintln!("Warning: Failed to configure thread pool: {}", e);
                 eprintln!("Using default thread pool configuration");
             });
-        println!("Using {} threads for token counting", cli.num_threads);
+        eprintln!("Using {} threads for token counting", cli.num_threads);
     } else {
-        println!("Using all available CPU cores for token counting");
+        eprintln!("Using all available CPU cores for token counting");
     }
+}
+
+// The path a counted file is recorded under in a TokenReport: relative to the innermost input
+// containing it, behind that input's name when there are several. Files outside every input
+// keep the path they were found at
+pub fn report_path(file: &Path, roots: &[PathBuf]) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let normalized = normalize_path(file, &cwd);
+    let Some((root, relative)) = roots
+        .iter()
+        .map(|root| normalize_path(root, &cwd))
+        .filter_map(|root| {
+            let relative = normalized.strip_prefix(&root).ok()?.to_path_buf();
+            Some((root, relative))
+        })
+        .max_by_key(|(root, _)| root.components().count())
+    else {
+        return file.to_path_buf();
+    };
+
+    let name = root.file_name().map(PathBuf::from).unwrap_or_default();
+    match (roots.len() > 1, relative.as_os_str().is_empty()) {
+        // The input is the file itself
+        (_, true) => normalized
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or(relative),
+        (true, false) => name.join(relative),
+        (false, false) => relative,
+    }
+}
+
+// Counts the selected files into a report, keyed by report_path
+pub fn count_tokens(cli: &cli::Cli, session: &Session) -> Result<TokenReport> {
+    // Initialize token report
+    let mut report = TokenReport::new();
 
     // Start the timer for token counting
     let start_time = Instant::now();
 
-    // Process files in parallel using a thread-safe approach
-    let paths = cli.paths.clone();
-    let cli_arc = Arc::new(cli.clone());
-
     // Create a thread-safe collection to hold results
-    let shared_results: Arc<Mutex<HashMap<PathBuf, usize>>> = Arc::new(Mutex::new(HashMap::new()));
-
-    // Initial discovery phase - collect all files to process
-    let mut all_files = Vec::new();
+    let shared_results: Arc<Mutex<FileResults>> = Arc::new(Mutex::new(HashMap::new()));
 
-    println!("Discovering files to process...");
+    eprintln!("Discovering files to process...");
     let discovery_progress = ProgressBar::new_spinner();
     discovery_progress.set_style(
         ProgressStyle::default_spinner()
@@ -293,106 +980,12 @@ pub fn process_token_count(cli: &cli::Cli) -> Result<()> {
     );
     discovery_progress.set_message("Scanning directories...");
 
-    for path in &paths {
-        if path.is_file() {
-            all_files.push(path.clone());
-            discovery_progress.set_message(format!("Found {} files", all_files.len()));
-            continue;
-        }
-
-        // Process a directory using WalkBuilder, which properly handles .gitignore files
-        let mut builder = WalkBuilder::new(path);
-
-        // Configure the builder based on CLI options
-        builder.follow_links(true);
-
-        // Control whether to respect .gitignore files
-        builder.git_ignore(!cli_arc.ignore_gitignore);
-        builder.git_global(!cli_arc.ignore_gitignore);
-
-        // Handle hidden files
-        builder.hidden(!cli_arc.include_hidden);
-
-        // Handle version control directories
-        if cli_arc.exclude_vcs && !cli_arc.include_vcs {
-            // Ignore .git directories
-            if !cli_arc.ignore_gitignore {
-                // The git_ignore setting already skips .git directories,
-                // but if we've disabled git_ignore, we need to add it manually
-                builder.filter_entry(|entry| {
-                    let path = entry.path();
-                    let file_name = path.file_name();
-                    if let Some(name) = file_name {
-                        // Skip .git, .svn, and .hg directories
-                        if name == ".git" || name == ".svn" || name == ".hg" {
-                            return !entry.file_type().is_some_and(|ft| ft.is_dir());
-                        }
-                    }
-                    true
-                });
-            }
-        }
-
-        // Process the entries using the walker
-        let walker = builder.build();
-
-        for result in walker {
-            match result {
-                Ok(entry) => {
-                    let entry_path = entry.path();
-                    discovery_progress.set_message(format!(
-                        "Scanning: {} (found {} files)",
-                        entry_path.display(),
-                        all_files.len()
-                    ));
-
-                    // Skip directories, we only want files
-                    if !entry
-                        .file_type()
-                        .unwrap_or_else(|| std::fs::metadata(entry_path).unwrap().file_type())
-                        .is_file()
-                    {
-                        continue;
-                    }
-
-                    // Check custom ignore patterns
-                    if should_ignore(
-                        entry_path,
-                        &cli_arc.ignore_patterns,
-                        cli_arc.ignore_files_only,
-                    ) {
-                        continue;
-                    }
-
-                    // Check extensions
-                    if !cli_arc.extensions.is_empty() {
-                        let extension = entry_path
-                            .extension()
-                            .and_then(|ext| ext.to_str())
-                            .unwrap_or("");
-
-                        if !cli_arc.extensions.iter().any(|ext| ext == extension) {
-                            continue;
-                        }
-                    }
-
-                    // Add valid file to our collection
-                    all_files.push(entry_path.to_path_buf());
-
-                    // Update progress message
-                    if all_files.len() % 100 == 0 {
-                        discovery_progress.set_message(format!("Found {} files", all_files.len()));
-                    }
-                }
-                Err(_) => continue,
-            }
-        }
-    }
+    let all_files = select_files(&cli.paths, cli, session)?;
 
     discovery_progress.finish_with_message(format!("Found {} files to process", all_files.len()));
 
     // Process all files with a progress bar
-    println!("Counting tokens in {} files...", all_files.len());
+    eprintln!("Counting tokens in {} files...", all_files.len());
     let progress = ProgressBar::new(all_files.len() as u64);
     progress.set_style(ProgressStyle::default_bar()
         .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) {msg}")
@@ -401,16 +994,52 @@ pub fn process_token_count(cli: &cli::Cli) -> Result<()> {
 
     // Use a counter to track total tokens
     let token_counter = Arc::new(Mutex::new(0usize));
+    let saved_counter = Arc::new(Mutex::new(0usize));
+
+    // --fit-report counts the content once with each tokenizer, so every model gets its own count
+    let fit_tokenizers = match cli.fit_report {
+        true => tokenizers::tokenizer_names(),
+        false => Vec::new(),
+    };
+    let tokenizer_totals: Arc<Mutex<HashMap<&str, usize>>> = Arc::new(Mutex::new(HashMap::new()));
 
     // Process each file in parallel
     all_files.par_iter().for_each(|file_path| {
-        match std::fs::read_to_string(file_path) {
-            Ok(content) => {
-                let token_count = tokenizers::count_tokens(&content, &cli_arc.tokenizer_model);
+        match session.read_to_string(file_path) {
+            Ok(original) => {
+                let model = &cli.tokenizer_model;
+                let content = if !rewrites_content(cli) {
+                    original
+                } else {
+                    let stripped = transform_content(original.clone(), file_path, cli, session);
+                    let saved = tokenizers::count_tokens(&original, model)
+                        .saturating_sub(tokenizers::count_tokens(&stripped, model));
+                    if let Ok(mut counter) = saved_counter.lock() {
+                        *counter += saved;
+                    }
+                    stripped
+                };
+                let token_count = tokenizers::count_tokens(&content, model);
+
+                for &tokenizer in &fit_tokenizers {
+                    let count = match tokenizer == tokenizers::get_tokenizer_name(model) {
+                        true => token_count,
+                        false => tokenizers::count_tokens_with(&content, tokenizer),
+                    };
+                    if let Ok(mut totals) = tokenizer_totals.lock() {
+                        *totals.entry(tokenizer).or_default() += count;
+                    }
+                }
 
                 // Update the shared results
                 if let Ok(mut results) = shared_results.lock() {
-                    results.insert(file_path.clone(), token_count);
+                    results.insert(
+                        file_path.clone(),
+                        (
+                            FileStats::from_content(&content, token_count),
+                            session.languages.detect(file_path, Some(&content)),
+                        ),
+                    );
                 }
 
                 // Update token counter and progress
@@ -436,16 +1065,68 @@ pub fn process_token_count(cli: &cli::Cli) -> Result<()> {
         .into_inner()
         .expect("Failed to unlock results");
 
-    for (path, token_count) in final_results {
-        report.add_file(path, token_count);
+    report.saved_tokens = saved_counter.lock().map_or(0, |counter| *counter);
+
+    for (path, (stats, language)) in final_results {
+        // Overlapping inputs find the same file twice
+        let key = report_path(&path, &cli.paths);
+        if report.files.contains_key(&key) {
+            continue;
+        }
+        if let Some(history) = session.file_history(&path).filter(|_| cli.git_metadata) {
+            report.histories.insert(key.clone(), history);
+        }
+        report.add_file(key, stats);
+        report.add_language(language.unwrap_or("other"), stats);
     }
 
     // Calculate and store the duration
     let duration = start_time.elapsed();
     report.set_duration(duration.as_millis());
+    report.set_model(&cli.tokenizer_model, &session.pricing);
 
-    // Display token counting results
-    display_token_report(&report, cli)?;
+    if cli.fit_report {
+        let totals = tokenizer_totals.lock().map(|totals| totals.clone());
+        report.context_fit =
+            ContextFit::for_all_models(&totals.unwrap_or_default(), &session.pricing);
+    }
 
-    Ok(())
+    Ok(report)
+}
+
+pub fn check_token_budget(report: &TokenReport, cli: &cli::Cli) -> Result<()> {
+    let budget = TokenBudget {
+        max_total: cli.max_tokens,
+        max_file: cli.max_file_tokens,
+        max_dir: cli.max_dir_tokens,
+    };
+
+    if budget.is_empty() {
+        return Ok(());
+    }
+
+    // Report paths are already relative to their input; with several inputs, each input's name
+    // is the root that --max-dir-tokens leaves out
+    let roots: Vec<PathBuf> = match cli.paths.len() > 1 {
+        true => cli
+            .paths
+            .iter()
+            .map(|root| report_path(root, std::slice::from_ref(root)))
+            .collect(),
+        false => Vec::new(),
+    };
+    let violations = budget.check(report, &roots);
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("Token budget exceeded:");
+    for violation in &violations {
+        eprintln!("  {}", violation);
+    }
+
+    Err(anyhow!(
+        "{} token budget violation(s) found",
+        violations.len()
+    ))
 }
@@ -1,10 +1,13 @@
 use anyhow::{anyhow, Result};
 use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use std::io::Read;
 
+use super::body::{streamed_body, CODEBASE};
 use super::models::{AnthropicContent, AnthropicMessage, AnthropicRequest, AnthropicResponse};
 
 pub fn summarize_with_anthropic(
-    code_content: &str,
+    code_content: impl Read + Send + 'static,
     prompt: &str,
     model: &str,
     api_key: &str,
@@ -17,7 +20,7 @@ pub fn summarize_with_anthropic(
             role: "user".to_string(),
             content: vec![AnthropicContent {
                 content_type: "text".to_string(),
-                text: format!("{}\n\nHere's the codebase:\n\n{}", prompt, code_content),
+                text: format!("{}\n\nHere's the codebase:\n\n{}", prompt, CODEBASE),
             }],
         }],
         max_tokens: 4096,
@@ -28,7 +31,8 @@ pub fn summarize_with_anthropic(
         .post("https://api.anthropic.com/v1/messages")
         .header("x-api-key", api_key)
         .header("anthropic-version", "2023-06-01")
-        .json(&request)
+        .header(CONTENT_TYPE, "application/json")
+        .body(streamed_body(&request, code_content)?)
         .send()?
         .json::<AnthropicResponse>()?;
 
@@ -0,0 +1,82 @@
+use anyhow::{anyhow, Result};
+use reqwest::blocking::Body;
+use serde::Serialize;
+use std::io::{self, Cursor, Read};
+
+// Stands in for the codebase in a request, which is streamed into its place when sent
+pub const CODEBASE: &str = "\u{0}codebase\u{0}";
+
+// The request serialized as JSON, with the codebase read from `codebase` into the string that
+// holds CODEBASE, so a large prompt is never assembled in memory
+pub fn streamed_json(
+    request: &impl Serialize,
+    codebase: impl Read + Send + 'static,
+) -> Result<impl Read + Send + 'static> {
+    let json = serde_json::to_string(request)?;
+    let marker = serde_json::to_string(CODEBASE)?;
+    let marker = &marker[1..marker.len() - 1];
+    let (prefix, suffix) = json
+        .split_once(marker)
+        .ok_or_else(|| anyhow!("Request has no place for the codebase"))?;
+
+    Ok(Cursor::new(prefix.to_string())
+        .chain(JsonEscape::new(codebase))
+        .chain(Cursor::new(suffix.to_string())))
+}
+
+pub fn streamed_body(
+    request: &impl Serialize,
+    codebase: impl Read + Send + 'static,
+) -> Result<Body> {
+    Ok(Body::new(streamed_json(request, codebase)?))
+}
+
+// Escapes text for the inside of a JSON string as it's read
+struct JsonEscape<R> {
+    inner: R,
+    escaped: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> JsonEscape<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            escaped: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for JsonEscape<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.escaped.len() {
+            let mut chunk = [0; 8192];
+            let read = self.inner.read(&mut chunk)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            self.escaped.clear();
+            self.position = 0;
+            for &byte in &chunk[..read] {
+                match byte {
+                    b'"' => self.escaped.extend_from_slice(b"\\\""),
+                    b'\\' => self.escaped.extend_from_slice(b"\\\\"),
+                    b'\n' => self.escaped.extend_from_slice(b"\\n"),
+                    b'\r' => self.escaped.extend_from_slice(b"\\r"),
+                    b'\t' => self.escaped.extend_from_slice(b"\\t"),
+                    // Multi-byte UTF-8 passes through as is, even when split across reads
+                    0..0x20 => self
+                        .escaped
+                        .extend_from_slice(format!("\\u{:04x}", byte).as_bytes()),
+                    _ => self.escaped.push(byte),
+                }
+            }
+        }
+
+        let count = buf.len().min(self.escaped.len() - self.position);
+        buf[..count].copy_from_slice(&self.escaped[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
@@ -1,21 +1,24 @@
 use anyhow::{anyhow, Result};
 use comfy_table::{ContentArrangement, Table};
 use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use std::io::Read;
 
+use super::body::{streamed_body, CODEBASE};
 use super::models::{
     GeminiConfig, GeminiListModelsResponse, GeminiMessage, GeminiPart, GeminiRequest,
     GeminiResponse,
 };
 
 pub fn summarize_with_gemini(
-    code_content: &str,
+    code_content: impl Read + Send + 'static,
     prompt: &str,
     model_name: &str,
     api_key: &str,
 ) -> Result<String> {
     let client = Client::new();
 
-    let full_prompt = format!("{}\n\nHere's the codebase:\n\n{}", prompt, code_content);
+    let full_prompt = format!("{}\n\nHere's the codebase:\n\n{}", prompt, CODEBASE);
 
     let request = GeminiRequest {
         contents: vec![GeminiMessage {
@@ -58,7 +61,12 @@ pub fn summarize_with_gemini(
     println!("Using API URL: {}", url.replace(api_key, "[REDACTED]"));
 
     // Send the request with timeout and error handling
-    let response = match client.post(&url).json(&request).send() {
+    let response = match client
+        .post(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(streamed_body(&request, code_content)?)
+        .send()
+    {
         Ok(resp) => resp,
         Err(e) => {
             return Err(anyhow!("Error sending request to Gemini API: {}", e));
@@ -177,13 +185,13 @@ pub fn list_gemini_models(api_key: &str) -> Result<()> {
     // Collect the names of standard models for later comparison
     let standard_model_names: Vec<String> = standard_models
         .iter()
-        .map(|m| m.name.split('/').last().unwrap_or(&m.name).to_string())
+        .map(|m| m.name.split('/').next_back().unwrap_or(&m.name).to_string())
         .collect();
 
     // Add standard models to table
     for model in &standard_models {
         // Extract the model name without the full path
-        let short_name = model.name.split('/').last().unwrap_or(&model.name);
+        let short_name = model.name.split('/').next_back().unwrap_or(&model.name);
 
         // Check if this is likely an experimental model
         let model_type = if short_name.contains("exp") {
@@ -210,7 +218,7 @@ pub fn list_gemini_models(api_key: &str) -> Result<()> {
     // Add experimental models to table
     for model in &experimental_models {
         // Extract the model name without the full path
-        let short_name = model.name.split('/').last().unwrap_or(&model.name);
+        let short_name = model.name.split('/').next_back().unwrap_or(&model.name);
 
         // Skip if this model already appears in the standard models list
         if standard_model_names.iter().any(|name| name == short_name) {
@@ -1,18 +1,24 @@
 mod anthropic;
+mod body;
 mod gemini;
 mod models;
 mod openai;
+mod prompts;
 
 pub use anthropic::summarize_with_anthropic;
+pub use body::{streamed_body, streamed_json, CODEBASE};
 pub use gemini::{list_gemini_models, summarize_with_gemini};
 pub use models::*;
 pub use openai::summarize_with_openai;
+pub use prompts::{CHANGELOG_MERGE_PROMPT, CHANGELOG_PROMPT, OVERVIEW_PROMPT, REVIEW_PROMPT};
 
 use crate::models::TokenizerModel;
 use anyhow::Result;
+use std::io::Read;
 
+// The codebase is streamed into the request as it's read, see streamed_body
 pub fn summarize_with_llm(
-    code_content: &str,
+    code_content: impl Read + Send + 'static,
     prompt: &str,
     model: &TokenizerModel,
     api_key: &str,
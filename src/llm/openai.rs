@@ -1,10 +1,13 @@
 use anyhow::{anyhow, Result};
 use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use std::io::Read;
 
+use super::body::{streamed_body, CODEBASE};
 use super::models::{OpenAIMessage, OpenAIRequest, OpenAIResponse};
 
 pub fn summarize_with_openai(
-    code_content: &str,
+    code_content: impl Read + Send + 'static,
     prompt: &str,
     model: &str,
     api_key: &str,
@@ -20,7 +23,7 @@ pub fn summarize_with_openai(
             },
             OpenAIMessage {
                 role: "user".to_string(),
-                content: code_content.to_string(),
+                content: CODEBASE.to_string(),
             },
         ],
         temperature: 0.7,
@@ -30,7 +33,8 @@ pub fn summarize_with_openai(
     let response = client
         .post("https://api.openai.com/v1/chat/completions")
         .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request)
+        .header(CONTENT_TYPE, "application/json")
+        .body(streamed_body(&request, code_content)?)
         .send()?
         .json::<OpenAIResponse>()?;
 
@@ -0,0 +1,11 @@
+// Used when summarizing whole files without --prompt
+pub const OVERVIEW_PROMPT: &str = "You are a senior software engineer reviewing a codebase. Generate a comprehensive overview.md file that explains the purpose, structure, and key components of this codebase. Focus on helping a new developer understand how the codebase is organized and how different parts work together.";
+
+// Used with --git-diff without --prompt
+pub const REVIEW_PROMPT: &str = "You are a senior software engineer reviewing a change. The input contains unified diffs of every touched file, and possibly their full content after the change. Write a code review in Markdown: start with a short summary of what the change does, then list concrete issues (bugs, edge cases, error handling, security, performance, missing tests) ordered by severity, each with the file and line it refers to and a suggested fix. Finish with smaller style or naming suggestions. Do not restate the diff, and say so plainly if you find no problems.";
+
+// Used with --changelog without --prompt, once per chunk of commits
+pub const CHANGELOG_PROMPT: &str = "You are a maintainer writing release notes. The input is a git log with commit messages, authors, changed files and possibly diffs. Write a changelog entry in Markdown using only the sections that apply, in this order: ### Added, ### Changed, ### Deprecated, ### Removed, ### Fixed, ### Security. Each item is one line describing a user-visible change in plain language, grouping related commits and leaving out merges, refactors, test-only and CI-only changes unless they matter to users. Mention breaking changes first in their section, prefixed with **Breaking:**. Do not add a version heading, introduction or closing remarks.";
+
+// Combines the entries written for each chunk when the history didn't fit in one request
+pub const CHANGELOG_MERGE_PROMPT: &str = "You are a maintainer writing release notes. The input contains several partial changelog entries for consecutive parts of the same release. Merge them into a single entry in the same Markdown format, keeping the section order ### Added, ### Changed, ### Deprecated, ### Removed, ### Fixed, ### Security, removing duplicates and combining items that describe the same change. Do not add a version heading, introduction or closing remarks.";
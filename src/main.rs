@@ -1,14 +1,106 @@
 use anyhow::Result;
 use clap::Parser;
+use std::io::Write;
 use std::path::PathBuf;
 use thousands::Separable;
 
+use summarize::archive::process_archive;
+use summarize::changelog::{summarize_history, write_changelog};
 use summarize::cli::Cli;
-use summarize::llm::{list_gemini_models, summarize_with_llm};
-use summarize::models::OutputFormat;
+use summarize::formatters::{Sink, Writer};
+use summarize::llm::{list_gemini_models, summarize_with_llm, OVERVIEW_PROMPT, REVIEW_PROMPT};
+use summarize::models::{OutputFormat, TokenizerModel};
+use summarize::session::Session;
 use summarize::tokenizers;
-use summarize::utils::{get_api_key, read_paths_from_stdin};
-use summarize::{collect_file_contents, process_token_count};
+use summarize::unpack::process_unpack;
+use summarize::utils::{get_api_key, load_path_map, read_paths_from_stdin, restore_paths};
+use summarize::{
+    output_sinks, process_revision_diff, process_token_count, process_token_diff,
+    write_file_contents,
+};
+
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+}
+
+// Writes the concatenated files to the requested outputs and any `extra` sinks
+fn write_prompt<'a>(
+    cli: &Cli,
+    session: &Session,
+    output_format: &OutputFormat,
+    extra: Vec<Sink<'a>>,
+) -> Result<()> {
+    let mut sinks: Vec<Sink<'a>> = output_sinks(cli)?;
+    sinks.extend(extra);
+    if sinks.is_empty() {
+        return Ok(());
+    }
+    let mut writer = Writer::from_sinks(sinks);
+    write_file_contents(&cli.paths, cli, session, output_format, &mut writer)?;
+    writer.finish()
+}
+
+fn report_output_file(cli: &Cli) {
+    if let Some(output_file) = &cli.output_file {
+        eprintln!("Concatenated content written to {}", output_file.display());
+    }
+}
+
+// Prompts are tokenized in pieces of about this many bytes
+const PROMPT_CHUNK_BYTES: usize = 1 << 20;
+
+// Measures the prompt as it streams past. The text is buffered and tokenized in large pieces
+// that end at a newline, since the writer's chunks may end inside a UTF-8 character or a
+// token; only the few tokens at those piece boundaries can differ from counting it whole
+struct PromptSize<'a> {
+    model: &'a TokenizerModel,
+    bytes: usize,
+    tokens: usize,
+    pending: Vec<u8>,
+}
+
+impl<'a> PromptSize<'a> {
+    fn new(model: &'a TokenizerModel) -> Self {
+        Self {
+            model,
+            bytes: 0,
+            tokens: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn count(&mut self, end: usize) {
+        let text = String::from_utf8_lossy(&self.pending[..end]);
+        self.tokens += tokenizers::count_tokens(&text, self.model);
+        self.pending.drain(..end);
+    }
+
+    // Bytes and estimated tokens
+    fn finish(mut self) -> (usize, usize) {
+        self.count(self.pending.len());
+        (self.bytes, self.tokens)
+    }
+}
+
+impl Write for PromptSize<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.bytes += buf.len();
+        self.pending.extend_from_slice(buf);
+        if self.pending.len() < PROMPT_CHUNK_BYTES {
+            return Ok(buf.len());
+        }
+        if let Some(newline) = self.pending.iter().rposition(|&byte| byte == b'\n') {
+            self.count(newline + 1);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
 
 fn main() -> Result<()> {
     let mut cli = Cli::parse();
@@ -33,6 +125,23 @@ fn main() -> Result<()> {
         return list_gemini_models(&api_key);
     }
 
+    // Comparing saved reports doesn't touch the file tree
+    if let [old_report, new_report] = cli.diff_reports.as_slice() {
+        return process_token_diff(old_report, new_report);
+    }
+
+    // Map displayed paths in an LLM answer back to the real ones
+    if let (Some(text_file), Some(path_map)) = (&cli.restore_paths, &cli.path_map) {
+        let text = std::fs::read_to_string(text_file)?;
+        print!("{}", restore_paths(&text, &load_path_map(path_map)?));
+        return Ok(());
+    }
+
+    // Unpacking reads a bundle instead of walking paths
+    if let Some(bundle) = &cli.unpack {
+        return process_unpack(bundle, &cli);
+    }
+
     // Read paths from stdin if available
     let mut stdin_paths = read_paths_from_stdin(cli.null)?;
 
@@ -44,44 +153,55 @@ fn main() -> Result<()> {
         cli.paths.push(PathBuf::from("."));
     }
 
+    // Each revision gets its own session, as with --rev
+    if let [old_revision, new_revision] = cli.diff_revs.as_slice() {
+        return process_revision_diff(&cli, old_revision, new_revision);
+    }
+
+    // With --rev, files come from the git object database instead of the working tree, and
+    // --git-metadata and --priority collect the file histories here
+    let session = Session::from_cli(&cli)?;
+
+    // Archives take the selected files as they are instead of concatenating them
+    if let Some(archive) = &cli.archive {
+        return process_archive(archive, &cli, &session);
+    }
+
     // If API key is needed, try to retrieve it
     let api_key = get_api_key(&cli);
 
     // For token counting, show a note if API key is missing but only for cost estimates
     if cli.count_tokens && cli.show_cost && api_key.is_none() {
-        println!("Note: No API key found. Cost estimates are based on published rates only.");
-        println!("To set an API key, use --api-key or --api-key-env options\n");
+        eprintln!("Note: No API key found. Cost estimates are based on published rates only.");
+        eprintln!("To set an API key, use --api-key or --api-key-env options\n");
     }
 
     // Special handling for token counting mode
     if cli.count_tokens {
-        return process_token_count(&cli);
+        return process_token_count(&cli, &session);
     }
 
     // Determine output format
-    let output_format = if cli.cxml {
-        OutputFormat::Cxml
-    } else if cli.markdown {
-        OutputFormat::Markdown
-    } else {
-        cli.output_format.clone()
-    };
+    let output_format = cli.format();
 
-    // Collect all file contents
-    let content = collect_file_contents(&cli.paths, &cli, &output_format)?;
+    // Without summarization, stream straight to the output file or stdout
+    if cli.no_summarize {
+        let mut sinks = output_sinks(&cli)?;
+        if sinks.is_empty() {
+            sinks.push(Sink::stdout());
+        }
+        let mut writer = Writer::from_sinks(sinks);
+        let written = write_file_contents(&cli.paths, &cli, &session, &output_format, &mut writer)
+            .and_then(|_| writer.finish());
 
-    // Output concatenated content to file if requested
-    if let Some(output_file) = &cli.output_file {
-        // Write to file
-        std::fs::write(output_file, &content)?;
-        println!("Concatenated content written to {}", output_file.display());
-    }
+        // A reader such as `head` closing the pipe early is not an error
+        match written {
+            Err(e) if is_broken_pipe(&e) => return Ok(()),
+            result => result?,
+        }
 
-    // If no-summarize, just output content to stdout if no output file specified
-    if cli.no_summarize {
-        if cli.output_file.is_none() {
-            // Write to stdout
-            print!("{}", content);
+        if let Some(output_file) = &cli.output_file {
+            eprintln!("Concatenated content written to {}", output_file.display());
         }
         return Ok(());
     }
@@ -89,25 +209,78 @@ fn main() -> Result<()> {
     // Default behavior: send the content to the LLM for summarization
 
     // Check for API key again since we need it for summarization
-    if api_key.is_none() {
+    let Some(api_key) = api_key else {
+        write_prompt(&cli, &session, &output_format, Vec::new())?;
+        report_output_file(&cli);
         eprintln!("Error: No API key found. An API key is required for summarization.");
         eprintln!(
             "Please provide an API key with --api-key or set the appropriate environment variable."
         );
         eprintln!("Use --no-summarize to only concatenate files without generating a summary.");
         std::process::exit(1);
-    }
+    };
 
     println!("Summarizing codebase with {} model...", cli.tokenizer_model);
 
-    // Get the API key
-    let api_key = api_key.unwrap();
+    // Release notes are written per chunk of history and added to the top of the changelog
+    if let Some(range) = &cli.changelog {
+        write_prompt(&cli, &session, &output_format, Vec::new())?;
+        report_output_file(&cli);
+        let entry = summarize_history(range, &cli, &api_key)?;
+        let changelog = cli
+            .summary_output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("CHANGELOG.md"));
+        write_changelog(&changelog, &entry)?;
+        println!("Changelog entry written to {}", changelog.display());
+        return Ok(());
+    }
+
+    // Diff output gets a code review unless a prompt was given
+    let reviewing = cli.git_diff.is_some();
+    let prompt = match &cli.custom_prompt {
+        Some(prompt) => prompt.as_str(),
+        None if reviewing => REVIEW_PROMPT,
+        None => OVERVIEW_PROMPT,
+    };
+    let summary_output = cli.summary_output.clone().unwrap_or_else(|| {
+        PathBuf::from(if reviewing {
+            "review.md"
+        } else {
+            "overview.md"
+        })
+    });
+
+    // The prompt is written through a pipe into the request body, and to the requested
+    // outputs on the way, so it's never held in memory as a whole
+    let (reader, pipe) = std::io::pipe()?;
+    let mut size = PromptSize::new(&cli.tokenizer_model);
+    let (written, summary) = std::thread::scope(|scope| {
+        let producer = scope.spawn(|| {
+            let sinks = vec![
+                Sink::Plain(Box::new(pipe)),
+                Sink::Plain(Box::new(&mut size)),
+            ];
+            write_prompt(&cli, &session, &output_format, sinks)
+        });
+        let summary = summarize_with_llm(reader, prompt, &cli.tokenizer_model, &api_key);
+        let written = producer.join().expect("Writing the prompt panicked");
+        (written, summary)
+    });
+
+    if let Err(e) = written {
+        // A failed request closes the pipe, which the writer then sees as a broken pipe
+        return Err(match summary {
+            Err(request) if is_broken_pipe(&e) => request,
+            _ => e,
+        });
+    }
+    report_output_file(&cli);
 
     // Log input size information
-    let input_size_bytes = content.len();
+    let (input_size_bytes, token_count) = size.finish();
     let input_size_kb = input_size_bytes / 1024;
     let input_size_mb = input_size_kb / 1024;
-    let token_count = tokenizers::count_tokens(&content, &cli.tokenizer_model);
 
     println!(
         "Input size: {} bytes ({} KB, {:.2} MB)",
@@ -120,13 +293,18 @@ fn main() -> Result<()> {
         token_count.separate_with_commas()
     );
 
-    // Get summary from LLM
-    let summary = summarize_with_llm(&content, &cli.custom_prompt, &cli.tokenizer_model, &api_key)?;
+    let summary = summary?;
+
+    // Point the summary at the real files again if paths were rewritten
+    let summary = match &cli.path_map {
+        Some(path_map) => restore_paths(&summary, &load_path_map(path_map)?),
+        None => summary,
+    };
 
     // Write summary to file
-    std::fs::write(&cli.summary_output, summary)?;
+    std::fs::write(&summary_output, summary)?;
 
-    println!("Summary written to {}", cli.summary_output.display());
+    println!("Summary written to {}", summary_output.display());
 
     Ok(())
 }
@@ -0,0 +1,32 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    // Optionally compressed, see Compression::from_path
+    Tar,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if name.ends_with(".zip") {
+            Ok(ArchiveFormat::Zip)
+        } else if [".tar", ".tar.gz", ".tgz", ".tar.zst", ".tzst"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+        {
+            Ok(ArchiveFormat::Tar)
+        } else {
+            Err(anyhow!(
+                "Unsupported archive {}: use .tar, .tar.gz, .tgz, .tar.zst, .tzst or .zip",
+                path.display()
+            ))
+        }
+    }
+}
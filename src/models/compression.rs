@@ -0,0 +1,23 @@
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    // Picked from the output file extension, e.g. bundle.txt.gz or bundle.tar.zst
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("gz" | "tgz") => Compression::Gzip,
+            Some("zst" | "tzst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{CostEstimate, TokenizerModel};
+use crate::tokenizers::{self, PricingTable};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextFit {
+    pub model: String,
+    // Input tokens as counted by this model's tokenizer
+    #[serde(default)]
+    pub tokens: usize,
+    pub context_window: usize,
+    pub fits: bool,
+    pub percent_used: f64,
+    // Tokens left in the context window for the response
+    pub headroom: usize,
+    pub cost: CostEstimate,
+}
+
+impl ContextFit {
+    pub fn for_model(model: &TokenizerModel, input_tokens: usize, pricing: &PricingTable) -> Self {
+        let context_window = tokenizers::get_context_window(model);
+
+        Self {
+            model: model.to_string(),
+            tokens: input_tokens,
+            context_window,
+            fits: input_tokens <= context_window,
+            percent_used: input_tokens as f64 * 100.0 / context_window as f64,
+            headroom: context_window.saturating_sub(input_tokens),
+            cost: CostEstimate::for_model(model, input_tokens, pricing),
+        }
+    }
+
+    // `tokens` holds the input counted by each tokenizer, keyed by tokenizers::get_tokenizer_name
+    pub fn for_all_models(tokens: &HashMap<&str, usize>, pricing: &PricingTable) -> Vec<Self> {
+        TokenizerModel::value_variants()
+            .iter()
+            .map(|model| {
+                let tokenizer = tokenizers::get_tokenizer_name(model);
+                Self::for_model(
+                    model,
+                    tokens.get(tokenizer).copied().unwrap_or_default(),
+                    pricing,
+                )
+            })
+            .collect()
+    }
+}
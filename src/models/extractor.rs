@@ -0,0 +1,14 @@
+use clap::ValueEnum;
+
+// Readers for file types whose raw content wastes tokens, applied with --extract
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Extractor {
+    // Jupyter notebooks, rendered as their cells with outputs truncated
+    Notebooks,
+    // CSV and TSV files, sampled to the header and the first rows
+    Tables,
+    // JSON and YAML files, pretty-printed and truncated below a nesting depth
+    Data,
+    // Package manager lockfiles, reduced to the packages they pin
+    Lockfiles,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::format_date;
+
+// Starts the line written above each file in Default and Markdown output
+pub const HISTORY_LINE_PREFIX: &str = "Git history: ";
+
+// How much a file has changed, from the commits that touched it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHistory {
+    // Commit time in seconds since the epoch
+    pub last_modified: i64,
+    pub commits: usize,
+    pub authors: usize,
+}
+
+impl FileHistory {
+    pub fn last_modified_date(&self) -> String {
+        format_date(self.last_modified)
+    }
+
+    pub fn summary_line(&self) -> String {
+        format!(
+            "{}last modified {}, {} commit{}, {} author{}",
+            HISTORY_LINE_PREFIX,
+            self.last_modified_date(),
+            self.commits,
+            if self.commits == 1 { "" } else { "s" },
+            self.authors,
+            if self.authors == 1 { "" } else { "s" }
+        )
+    }
+}
@@ -0,0 +1,10 @@
+// Restricts the selected files to those git reports as changed
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitSelection {
+    // Changed on this branch since it forked from the ref, including uncommitted work
+    ChangedSince(String),
+    // Staged in the index but not yet committed
+    Staged,
+    // Staged, unstaged and untracked changes against HEAD
+    Uncommitted,
+}
@@ -1,7 +1,31 @@
+mod archive_format;
+mod compression;
+mod context_fit;
+mod extractor;
+mod file_history;
+mod git_selection;
 mod output_format;
+mod path_mode;
+mod priority;
+mod report_format;
+mod token_budget;
+mod token_diff;
 mod token_report;
 mod tokenizer_model;
+mod transform;
 
+pub use archive_format::ArchiveFormat;
+pub use compression::Compression;
+pub use context_fit::ContextFit;
+pub use extractor::Extractor;
+pub use file_history::{FileHistory, HISTORY_LINE_PREFIX};
+pub use git_selection::GitSelection;
 pub use output_format::OutputFormat;
-pub use token_report::TokenReport;
+pub use path_mode::PathMode;
+pub use priority::Priority;
+pub use report_format::ReportFormat;
+pub use token_budget::{BudgetViolation, TokenBudget};
+pub use token_diff::{DiffStatus, FileDiff, TokenDiff};
+pub use token_report::{CostEstimate, FileStats, TokenReport};
 pub use tokenizer_model::TokenizerModel;
+pub use transform::Transform;
@@ -5,4 +5,7 @@ pub enum OutputFormat {
     Default,
     Cxml,
     Markdown,
+    Json,
+    Jsonl,
+    Template,
 }
@@ -0,0 +1,13 @@
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, PartialEq, ValueEnum)]
+pub enum PathMode {
+    // Paths exactly as they were walked
+    Walked,
+    // Relative to --path-root (the current directory by default)
+    Relative,
+    // Relative to the root of the enclosing git repository
+    GitRoot,
+    // Every path component replaced by a short hash keyed with a random per-run key
+    Anonymized,
+}
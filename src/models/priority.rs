@@ -0,0 +1,25 @@
+use clap::ValueEnum;
+
+use super::FileHistory;
+
+// Orders files so the most active ones come first, and survive if the output is cut short
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Priority {
+    // Most commits first
+    Churn,
+    // Most recently modified first
+    Recent,
+    // Most authors first
+    Authors,
+}
+
+impl Priority {
+    // Higher sorts first
+    pub fn score(&self, history: &FileHistory) -> i64 {
+        match self {
+            Priority::Churn => history.commits as i64,
+            Priority::Recent => history.last_modified,
+            Priority::Authors => history.authors as i64,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use super::TokenReport;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TokenBudget {
+    pub max_total: Option<usize>,
+    pub max_file: Option<usize>,
+    pub max_dir: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetViolation {
+    Total {
+        tokens: usize,
+        limit: usize,
+    },
+    File {
+        path: PathBuf,
+        tokens: usize,
+        limit: usize,
+    },
+    Directory {
+        path: PathBuf,
+        tokens: usize,
+        limit: usize,
+    },
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetViolation::Total { tokens, limit } => {
+                write!(f, "total: {} tokens (limit {})", tokens, limit)
+            }
+            BudgetViolation::File {
+                path,
+                tokens,
+                limit,
+            } => write!(
+                f,
+                "file {}: {} tokens (limit {})",
+                path.display(),
+                tokens,
+                limit
+            ),
+            BudgetViolation::Directory {
+                path,
+                tokens,
+                limit,
+            } => write!(
+                f,
+                "directory {}: {} tokens (limit {})",
+                path.display(),
+                tokens,
+                limit
+            ),
+        }
+    }
+}
+
+impl TokenBudget {
+    pub fn is_empty(&self) -> bool {
+        self.max_total.is_none() && self.max_file.is_none() && self.max_dir.is_none()
+    }
+
+    // `roots` are the input paths, which --max-dir-tokens doesn't apply to
+    pub fn check(&self, report: &TokenReport, roots: &[PathBuf]) -> Vec<BudgetViolation> {
+        let mut violations = Vec::new();
+
+        if let Some(limit) = self.max_total {
+            if report.total_tokens > limit {
+                violations.push(BudgetViolation::Total {
+                    tokens: report.total_tokens,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = self.max_file {
+            for (path, stats) in &report.files {
+                if stats.tokens > limit {
+                    violations.push(BudgetViolation::File {
+                        path: path.clone(),
+                        tokens: stats.tokens,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        if let Some(limit) = self.max_dir {
+            for (path, tokens) in report.directory_tokens(roots) {
+                if tokens > limit {
+                    violations.push(BudgetViolation::Directory {
+                        path,
+                        tokens,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+}
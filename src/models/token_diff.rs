@@ -0,0 +1,82 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::TokenReport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileDiff {
+    pub path: PathBuf,
+    pub status: DiffStatus,
+    pub old_tokens: usize,
+    pub new_tokens: usize,
+    pub delta: i64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TokenDiff {
+    pub old_total: usize,
+    pub new_total: usize,
+    pub delta: i64,
+    // Changed files, largest absolute delta first
+    pub files: Vec<FileDiff>,
+    pub unchanged: usize,
+}
+
+impl TokenDiff {
+    pub fn between(old: &TokenReport, new: &TokenReport) -> Self {
+        let mut files = Vec::new();
+        let mut unchanged = 0;
+
+        let mut paths: Vec<&PathBuf> = old.files.keys().chain(new.files.keys()).collect();
+        paths.sort();
+        paths.dedup();
+
+        for path in paths {
+            let old_stats = old.files.get(path);
+            let new_stats = new.files.get(path);
+            let old_tokens = old_stats.map_or(0, |s| s.tokens);
+            let new_tokens = new_stats.map_or(0, |s| s.tokens);
+
+            let status = match (old_stats, new_stats) {
+                (None, Some(_)) => DiffStatus::Added,
+                (Some(_), None) => DiffStatus::Removed,
+                _ if new_tokens > old_tokens => DiffStatus::Grown,
+                _ if new_tokens < old_tokens => DiffStatus::Shrunk,
+                _ => DiffStatus::Unchanged,
+            };
+
+            if status == DiffStatus::Unchanged {
+                unchanged += 1;
+                continue;
+            }
+
+            files.push(FileDiff {
+                path: path.clone(),
+                status,
+                old_tokens,
+                new_tokens,
+                delta: new_tokens as i64 - old_tokens as i64,
+            });
+        }
+
+        // Stable sort keeps path order among equal deltas
+        files.sort_by_key(|f| std::cmp::Reverse(f.delta.unsigned_abs()));
+
+        Self {
+            old_total: old.total_tokens,
+            new_total: new.total_tokens,
+            delta: new.total_tokens as i64 - old.total_tokens as i64,
+            files,
+            unchanged,
+        }
+    }
+}
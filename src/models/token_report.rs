@@ -1,29 +1,205 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Default)]
+use super::{ContextFit, FileHistory, TokenizerModel};
+use crate::tokenizers::{self, PricingTable};
+
+// Share of the input size assumed for the response when estimating output cost
+pub const ESTIMATED_OUTPUT_RATIO: f64 = 0.2;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FileStats {
+    pub tokens: usize,
+    pub bytes: usize,
+    pub lines: usize,
+}
+
+impl FileStats {
+    pub fn from_content(content: &str, tokens: usize) -> Self {
+        Self {
+            tokens,
+            bytes: content.len(),
+            lines: content.lines().count(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub input_cost_per_1k: f64,
+    pub output_cost_per_1k: f64,
+    pub input_cost: f64,
+    pub estimated_output_tokens: usize,
+    pub output_cost: f64,
+    pub total_cost: f64,
+    // Per-1k rate for input served from the provider's context cache
+    #[serde(default)]
+    pub cached_input_cost_per_1k: Option<f64>,
+    // Total cost when submitted through the provider's batch API
+    #[serde(default)]
+    pub batch_total_cost: Option<f64>,
+}
+
+impl CostEstimate {
+    pub fn for_model(model: &TokenizerModel, input_tokens: usize, pricing: &PricingTable) -> Self {
+        let (input_cost_per_1k, output_cost_per_1k) =
+            tokenizers::get_token_cost(model, input_tokens, pricing);
+        let input_cost = (input_tokens as f64 / 1000.0) * input_cost_per_1k;
+
+        let estimated_output_tokens =
+            (input_tokens as f64 * ESTIMATED_OUTPUT_RATIO).round() as usize;
+        let output_cost = (estimated_output_tokens as f64 / 1000.0) * output_cost_per_1k;
+
+        let total_cost = input_cost + output_cost;
+
+        Self {
+            input_cost_per_1k,
+            output_cost_per_1k,
+            input_cost,
+            estimated_output_tokens,
+            output_cost,
+            total_cost,
+            cached_input_cost_per_1k: tokenizers::get_cached_input_cost(
+                model,
+                input_tokens,
+                pricing,
+            ),
+            batch_total_cost: tokenizers::get_batch_discount(model, pricing)
+                .map(|discount| total_cost * (1.0 - discount)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TokenReport {
-    pub file_tokens: HashMap<PathBuf, usize>,
+    // Keyed by the path relative to the input it was found under, so reports of two checkouts
+    // or revisions line up. With several inputs, the input's name is kept in front
+    pub files: BTreeMap<PathBuf, FileStats>,
     pub total_tokens: usize,
+    pub total_bytes: usize,
+    pub total_lines: usize,
     // Duration in milliseconds
     pub duration_ms: u128,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub tokenizer: Option<String>,
+    #[serde(default)]
+    pub cost: Option<CostEstimate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_fit: Vec<ContextFit>,
+    // Totals per detected language; files without one are counted as "other"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub languages: BTreeMap<String, FileStats>,
+    // Tokens removed by --outline and --strip; the totals above are counted after them
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub saved_tokens: usize,
+    // Git history of each file for --git-metadata, keyed like `files`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub histories: BTreeMap<PathBuf, FileHistory>,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
 }
 
 impl TokenReport {
     pub fn new() -> Self {
-        Self {
-            file_tokens: HashMap::new(),
-            total_tokens: 0,
-            duration_ms: 0,
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, path: PathBuf, stats: FileStats) {
+        if let Some(previous) = self.files.insert(path, stats) {
+            self.total_tokens -= previous.tokens;
+            self.total_bytes -= previous.bytes;
+            self.total_lines -= previous.lines;
         }
+        self.total_tokens += stats.tokens;
+        self.total_bytes += stats.bytes;
+        self.total_lines += stats.lines;
     }
 
-    pub fn add_file(&mut self, path: PathBuf, token_count: usize) {
-        self.file_tokens.insert(path, token_count);
-        self.total_tokens += token_count;
+    pub fn add_language(&mut self, language: &str, stats: FileStats) {
+        let rollup = self.languages.entry(language.to_string()).or_default();
+        rollup.tokens += stats.tokens;
+        rollup.bytes += stats.bytes;
+        rollup.lines += stats.lines;
     }
 
     pub fn set_duration(&mut self, duration_ms: u128) {
         self.duration_ms = duration_ms;
     }
+
+    pub fn set_model(&mut self, model: &TokenizerModel, pricing: &PricingTable) {
+        self.model = Some(model.to_string());
+        self.tokenizer = Some(tokenizers::get_tokenizer_name(model).to_string());
+        self.cost = Some(CostEstimate::for_model(model, self.total_tokens, pricing));
+    }
+
+    // Rolls file tokens up into the directories between each file and the input root it was
+    // found under, leaving out the root itself. Files outside every root roll up into all of
+    // their ancestors but the filesystem root
+    pub fn directory_tokens(&self, roots: &[PathBuf]) -> BTreeMap<PathBuf, usize> {
+        let mut directories = BTreeMap::new();
+        for (path, stats) in &self.files {
+            let root = roots
+                .iter()
+                .filter(|root| path.starts_with(root))
+                .max_by_key(|root| root.components().count());
+            for ancestor in path.ancestors().skip(1) {
+                let at_root = match root {
+                    Some(root) => ancestor == root.as_path(),
+                    None => ancestor.parent().is_none(),
+                };
+                if at_root || ancestor.as_os_str().is_empty() {
+                    break;
+                }
+                *directories.entry(ancestor.to_path_buf()).or_insert(0) += stats.tokens;
+            }
+        }
+        directories
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read token report {}", path.display()))?;
+        Self::from_json(&json)
+            .with_context(|| format!("Failed to parse token report {}", path.display()))
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("path,tokens,bytes,lines\n");
+        for (path, stats) in &self.files {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                csv_field(&path.to_string_lossy()),
+                stats.tokens,
+                stats.bytes,
+                stats.lines
+            ));
+        }
+        csv.push_str(&format!(
+            "TOTAL,{},{},{}\n",
+            self.total_tokens, self.total_bytes, self.total_lines
+        ));
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
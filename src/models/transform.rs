@@ -0,0 +1,14 @@
+use clap::ValueEnum;
+
+// Content rewrites applied with --strip to save tokens
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Transform {
+    // Comments, keeping string literals that contain comment markers
+    Comments,
+    // A license or copyright comment at the top of the file
+    License,
+    // Whitespace at the end of every line
+    TrailingWhitespace,
+    // Runs of blank lines, collapsed into one
+    BlankLines,
+}
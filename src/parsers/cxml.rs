@@ -0,0 +1,138 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+use super::ParsedFile;
+
+const CDATA_START: &str = "<![CDATA[";
+const CDATA_END: &str = "]]>";
+
+pub fn unescape_xml(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        unescaped.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest.find(';').and_then(|semi| {
+            let entity = &rest[1..semi];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(char::from_u32),
+                _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            c.map(|c| (c, semi + 1))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                unescaped.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                // Not an entity we know, keep the ampersand as written
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+// Finds the next `<document ...>` or `<document>` tag, skipping `<documents>` and
+// `<document_content>`
+fn find_document_start(input: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    while let Some(pos) = input[offset..].find("<document") {
+        let start = offset + pos;
+        let after = &input[start + "<document".len()..];
+        if after.starts_with('>') || after.starts_with(char::is_whitespace) {
+            let end = start + input[start..].find('>')? + 1;
+            return Some((start, end));
+        }
+        offset = start + "<document".len();
+    }
+    None
+}
+
+fn parse_content(input: &str) -> Result<(String, usize)> {
+    let body = input.strip_prefix('\n').unwrap_or(input);
+    let mut consumed = input.len() - body.len();
+
+    if !body.starts_with(CDATA_START) {
+        // Raw content as produced before CDATA wrapping, taken verbatim
+        let end = body
+            .find("</document_content>")
+            .ok_or_else(|| anyhow!("Unterminated <document_content>"))?;
+        let raw = &body[..end];
+        let raw = raw.strip_suffix('\n').unwrap_or(raw);
+        return Ok((raw.to_string(), consumed + end));
+    }
+
+    // Concatenate adjacent CDATA sections, which is how `]]>` gets split
+    let mut content = String::new();
+    let mut rest = body;
+    while let Some(section) = rest.strip_prefix(CDATA_START) {
+        let end = section
+            .find(CDATA_END)
+            .ok_or_else(|| anyhow!("Unterminated CDATA section"))?;
+        content.push_str(&section[..end]);
+        let advanced = CDATA_START.len() + end + CDATA_END.len();
+        consumed += advanced;
+        rest = &rest[advanced..];
+    }
+
+    let trailing = rest
+        .find("</document_content>")
+        .ok_or_else(|| anyhow!("Expected </document_content> after CDATA section"))?;
+    if !rest[..trailing].trim().is_empty() {
+        return Err(anyhow!("Unexpected text after CDATA section"));
+    }
+
+    Ok((content, consumed + trailing))
+}
+
+pub fn parse_cxml(input: &str) -> Result<Vec<ParsedFile>> {
+    let mut files = Vec::new();
+    let mut rest = input;
+
+    while let Some((_, tag_end)) = find_document_start(rest) {
+        rest = &rest[tag_end..];
+
+        let source_start = rest
+            .find("<source>")
+            .ok_or_else(|| anyhow!("Document is missing <source>"))?
+            + "<source>".len();
+        let source_end = source_start
+            + rest[source_start..]
+                .find("</source>")
+                .ok_or_else(|| anyhow!("Unterminated <source>"))?;
+        let path = PathBuf::from(unescape_xml(rest[source_start..source_end].trim()));
+        rest = &rest[source_end..];
+
+        let content_start = rest
+            .find("<document_content>")
+            .ok_or_else(|| anyhow!("Document {} is missing <document_content>", path.display()))?
+            + "<document_content>".len();
+        let (content, consumed) = parse_content(&rest[content_start..])?;
+        rest = &rest[content_start + consumed + "</document_content>".len()..];
+
+        let document_end = rest
+            .find("</document>")
+            .ok_or_else(|| anyhow!("Document {} is missing </document>", path.display()))?;
+        rest = &rest[document_end + "</document>".len()..];
+
+        files.push(ParsedFile { path, content });
+    }
+
+    Ok(files)
+}
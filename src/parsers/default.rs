@@ -0,0 +1,50 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+use super::ParsedFile;
+use crate::models::HISTORY_LINE_PREFIX;
+
+// A file ends with an empty line and `---`, followed by the next `path` / `---` pair or EOF
+fn is_terminator(lines: &[&str], j: usize) -> bool {
+    if lines.get(j) != Some(&"") || lines.get(j + 1) != Some(&"---") {
+        return false;
+    }
+
+    // The next file may start with its --git-metadata line
+    let mut rest = &lines[j + 2..];
+    if rest
+        .first()
+        .is_some_and(|line| line.starts_with(HISTORY_LINE_PREFIX))
+    {
+        rest = &rest[1..];
+    }
+    rest.iter().all(|line| line.is_empty())
+        || (rest.len() >= 2 && !rest[0].is_empty() && rest[1] == "---")
+}
+
+pub fn parse_default(input: &str) -> Result<Vec<ParsedFile>> {
+    let lines: Vec<&str> = input.split('\n').collect();
+    let mut files = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        // Skip blank lines and any preamble such as a directory tree
+        if lines[i].is_empty() || lines.get(i + 1) != Some(&"---") {
+            i += 1;
+            continue;
+        }
+
+        let path = PathBuf::from(lines[i]);
+        let end = (i + 2..lines.len())
+            .find(|&j| is_terminator(&lines, j))
+            .ok_or_else(|| anyhow!("File {} is missing its closing '---'", path.display()))?;
+
+        files.push(ParsedFile {
+            path,
+            content: lines[i + 2..end].join("\n"),
+        });
+        i = end + 2;
+    }
+
+    Ok(files)
+}
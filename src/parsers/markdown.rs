@@ -0,0 +1,56 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+use super::ParsedFile;
+
+fn fence_length(line: &str) -> usize {
+    line.chars().take_while(|&c| c == '`').count()
+}
+
+// Models often decorate the path line with headings, bold or inline code
+fn clean_path_line(line: &str) -> &str {
+    line.trim()
+        .trim_start_matches('#')
+        .trim()
+        .trim_matches('*')
+        .trim_matches('`')
+        .trim()
+}
+
+pub fn parse_markdown(input: &str) -> Result<Vec<ParsedFile>> {
+    let lines: Vec<&str> = input.split('\n').collect();
+    let mut files = Vec::new();
+    let mut i = 0;
+
+    while i + 1 < lines.len() {
+        // A code block without a path line (such as a directory tree) isn't a file
+        let unlabeled = fence_length(lines[i]);
+        if unlabeled >= 3 {
+            let closing = &lines[i][..unlabeled];
+            i = (i + 1..lines.len())
+                .find(|&j| lines[j].trim_end() == closing)
+                .map_or(lines.len(), |j| j + 1);
+            continue;
+        }
+
+        let fence = fence_length(lines[i + 1]);
+        if lines[i].trim().is_empty() || fence < 3 {
+            i += 1;
+            continue;
+        }
+
+        let path = PathBuf::from(clean_path_line(lines[i]));
+        let closing = &lines[i + 1][..fence];
+        let end = (i + 2..lines.len())
+            .find(|&j| lines[j].trim_end() == closing)
+            .ok_or_else(|| anyhow!("File {} has an unterminated code fence", path.display()))?;
+
+        files.push(ParsedFile {
+            path,
+            content: lines[i + 2..end].join("\n"),
+        });
+        i = end + 1;
+    }
+
+    Ok(files)
+}
@@ -0,0 +1,68 @@
+mod cxml;
+mod default;
+mod markdown;
+
+pub use cxml::{parse_cxml, unescape_xml};
+pub use default::parse_default;
+pub use markdown::parse_markdown;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::models::{OutputFormat, HISTORY_LINE_PREFIX};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ParsedFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+// Guesses which of the text formats produced `input` from how it starts, since file contents
+// further down may look like any of them. Anything unrecognized is read as the default format,
+// whose parser skips a preamble such as a directory tree
+pub fn detect_format(input: &str) -> OutputFormat {
+    let lines: Vec<&str> = input.lines().collect();
+    let Some(first) = lines
+        .iter()
+        .position(|line| !line.trim().is_empty() && !line.starts_with(HISTORY_LINE_PREFIX))
+    else {
+        return OutputFormat::Default;
+    };
+    let line = lines[first].trim();
+
+    if line.starts_with("<documents>") || line.starts_with("<document ") {
+        return OutputFormat::Cxml;
+    }
+    // A default-format path may start with a bracket too, so JSON has to parse
+    if line.starts_with('[') && serde_json::from_str::<Vec<ParsedFile>>(input).is_ok() {
+        return OutputFormat::Json;
+    }
+    if line.starts_with('{') && serde_json::from_str::<ParsedFile>(line).is_ok() {
+        return OutputFormat::Jsonl;
+    }
+
+    // Markdown opens with a fence, either for the tree or after the first path
+    let next = lines.get(first + 1).copied().unwrap_or("");
+    if line.starts_with("```") || next.starts_with("```") {
+        OutputFormat::Markdown
+    } else {
+        OutputFormat::Default
+    }
+}
+
+pub fn parse_output(input: &str, format: &OutputFormat) -> Result<Vec<ParsedFile>> {
+    match format {
+        OutputFormat::Cxml => parse_cxml(input),
+        OutputFormat::Markdown => parse_markdown(input),
+        OutputFormat::Default => parse_default(input),
+        // Extra fields such as language and tokens are ignored
+        OutputFormat::Json => Ok(serde_json::from_str(input)?),
+        OutputFormat::Jsonl => input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect(),
+        OutputFormat::Template => Err(anyhow!("Output from custom templates can't be parsed")),
+    }
+}
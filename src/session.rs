@@ -0,0 +1,83 @@
+use anyhow::Result;
+use std::io;
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::git::{FileHistories, Snapshot, SnapshotOptions};
+use crate::languages::LanguageOverrides;
+use crate::models::FileHistory;
+use crate::tokenizers::{load_pricing, PricingTable};
+
+// State loaded once from the command line and passed alongside it to whatever discovers or
+// reads files. The default session reads the working tree
+#[derive(Default)]
+pub struct Session {
+    // Set by --rev and --staged: files are discovered and read from this commit or the index
+    // instead of the working tree
+    pub snapshot: Option<Snapshot>,
+    // Collected for --git-metadata and --priority, at --rev or HEAD
+    pub histories: Option<FileHistories>,
+    // Built-in prices with --pricing or the config-dir pricing.json merged over them
+    pub pricing: PricingTable,
+    // --language rules and the config-dir languages.json
+    pub languages: LanguageOverrides,
+}
+
+impl Session {
+    pub fn from_cli(cli: &Cli) -> Result<Self> {
+        // Inputs that only exist at --rev or in the index are looked up from the current directory
+        let starts: Vec<&Path> = cli
+            .paths
+            .iter()
+            .map(|path| match path.exists() {
+                true => path.as_path(),
+                false => Path::new("."),
+            })
+            .collect();
+        let options = SnapshotOptions {
+            gitignore: !cli.ignore_gitignore,
+            hidden: cli.include_hidden,
+        };
+        let snapshot = match (&cli.rev, cli.staged) {
+            (Some(revision), _) => {
+                let start = starts.first().copied().unwrap_or(Path::new("."));
+                Some(Snapshot::open(start, revision, options)?)
+            }
+            // --staged reads what is staged rather than the working tree
+            (None, true) => Some(Snapshot::from_index(starts.iter().copied(), options)?),
+            (None, false) => None,
+        };
+
+        let histories = match cli.git_metadata || cli.priority.is_some() {
+            true => {
+                let revision = cli.rev.as_deref().unwrap_or("HEAD");
+                Some(FileHistories::collect(starts.iter().copied(), revision)?)
+            }
+            false => None,
+        };
+
+        Ok(Self {
+            snapshot,
+            histories,
+            pricing: load_pricing(cli.pricing_file.as_deref())?,
+            languages: LanguageOverrides::load(&cli.language_overrides)?,
+        })
+    }
+
+    pub fn file_history(&self, path: &Path) -> Option<FileHistory> {
+        self.histories.as_ref()?.get(path)
+    }
+
+    // Reads from the --rev or --staged snapshot when there is one, otherwise from disk
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match &self.snapshot {
+            Some(snapshot) => snapshot.read(path),
+            None => std::fs::read(path),
+        }
+    }
+
+    pub fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
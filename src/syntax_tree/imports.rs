@@ -0,0 +1,217 @@
+use tree_sitter::Node;
+
+use super::parse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Import {
+    // `mod name;`, declared inside the inline modules in `parents`
+    RustModule {
+        parents: Vec<String>,
+        name: String,
+    },
+    // One path of a `use` declaration, with braces expanded and aliases dropped
+    RustUse {
+        parents: Vec<String>,
+        path: Vec<String>,
+    },
+    // `import a.b` has level 0 and no names; `from ..a import b, c` has level 2
+    Python {
+        level: usize,
+        module: Vec<String>,
+        names: Vec<String>,
+    },
+    // A JavaScript or TypeScript module specifier, or a Go import path
+    Path(String),
+}
+
+// The imports of a file, wherever they appear in it. Empty for languages without a grammar
+pub fn imports(content: &str, language: &str) -> Vec<Import> {
+    let Some(tree) = parse(content, language) else {
+        return Vec::new();
+    };
+    let mut imports = Vec::new();
+    collect_imports(
+        tree.root_node(),
+        content,
+        language,
+        &mut Vec::new(),
+        &mut imports,
+    );
+    imports
+}
+
+fn collect_imports(
+    node: Node,
+    content: &str,
+    language: &str,
+    parents: &mut Vec<String>,
+    imports: &mut Vec<Import>,
+) {
+    let text = |node: Node| content[node.byte_range()].to_string();
+
+    match (language, node.kind()) {
+        ("rust", "mod_item") => {
+            let Some(name) = node.child_by_field_name("name").map(text) else {
+                return;
+            };
+            match node.child_by_field_name("body") {
+                Some(body) => {
+                    parents.push(name);
+                    collect_imports(body, content, language, parents, imports);
+                    parents.pop();
+                }
+                None => imports.push(Import::RustModule {
+                    parents: parents.clone(),
+                    name,
+                }),
+            }
+            return;
+        }
+        ("rust", "use_declaration") => {
+            if let Some(argument) = node.child_by_field_name("argument") {
+                imports.extend(expand_use(&text(argument)).into_iter().map(|path| {
+                    Import::RustUse {
+                        parents: parents.clone(),
+                        path,
+                    }
+                }));
+            }
+            return;
+        }
+        ("python", "import_statement") => {
+            for name in field_children(node, "name") {
+                imports.push(Import::Python {
+                    level: 0,
+                    module: dotted(&text(import_name(name))),
+                    names: Vec::new(),
+                });
+            }
+            return;
+        }
+        ("python", "import_from_statement") => {
+            let module = node.child_by_field_name("module_name").map(text);
+            let module = module.unwrap_or_default();
+            let names = field_children(node, "name")
+                .into_iter()
+                .map(|name| text(import_name(name)))
+                .collect();
+            imports.push(Import::Python {
+                level: module.len() - module.trim_start_matches('.').len(),
+                module: dotted(module.trim_start_matches('.')),
+                names,
+            });
+            return;
+        }
+        (language, "import_statement" | "export_statement") if is_javascript(language) => {
+            if let Some(source) = node.child_by_field_name("source") {
+                imports.push(Import::Path(unquote(&text(source))));
+            }
+        }
+        (language, "call_expression") if is_javascript(language) => {
+            let function = node.child_by_field_name("function");
+            let is_import = function.is_some_and(|function| {
+                function.kind() == "import" || &content[function.byte_range()] == "require"
+            });
+            let argument = node
+                .child_by_field_name("arguments")
+                .and_then(|arguments| arguments.named_child(0))
+                .filter(|argument| argument.kind() == "string");
+            if let (true, Some(argument)) = (is_import, argument) {
+                imports.push(Import::Path(unquote(&text(argument))));
+            }
+        }
+        ("go", "import_spec") => {
+            if let Some(path) = node.child_by_field_name("path") {
+                imports.push(Import::Path(unquote(&text(path))));
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_imports(child, content, language, parents, imports);
+    }
+}
+
+fn is_javascript(language: &str) -> bool {
+    matches!(language, "javascript" | "jsx" | "typescript" | "tsx")
+}
+
+fn field_children<'tree>(node: Node<'tree>, field: &str) -> Vec<Node<'tree>> {
+    let mut cursor = node.walk();
+    node.children_by_field_name(field, &mut cursor).collect()
+}
+
+// The `a.b` of `a.b as c`
+fn import_name(node: Node) -> Node {
+    match node.kind() {
+        "aliased_import" => node.child_by_field_name("name").unwrap_or(node),
+        _ => node,
+    }
+}
+
+fn dotted(name: &str) -> Vec<String> {
+    name.split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn unquote(literal: &str) -> String {
+    literal.trim_matches(['"', '\'', '`']).to_string()
+}
+
+// Expands `a::{b, c::{d as e, self}}` into a::b, a::c::d and a::c
+pub fn expand_use(tree: &str) -> Vec<Vec<String>> {
+    let tree: String = tree.split_whitespace().collect::<Vec<_>>().join(" ");
+    let tree = tree.trim().trim_start_matches("::");
+
+    let Some(open) = tree.find('{') else {
+        let path = tree.split(" as ").next().unwrap_or_default();
+        let mut segments: Vec<String> = path
+            .split("::")
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty() && *segment != "*")
+            .map(str::to_string)
+            .collect();
+        if segments.last().is_some_and(|last| last == "self") {
+            segments.pop();
+        }
+        return vec![segments];
+    };
+
+    let prefix: Vec<String> = tree[..open]
+        .split("::")
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect();
+    let inner = tree[open + 1..]
+        .rfind('}')
+        .map_or(&tree[open + 1..], |close| &tree[open + 1..open + 1 + close]);
+
+    // Split on commas outside nested braces
+    let mut items = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (index, ch) in inner.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+
+    items
+        .into_iter()
+        .filter(|item| !item.trim().is_empty())
+        .flat_map(expand_use)
+        .map(|rest| prefix.iter().cloned().chain(rest).collect())
+        .collect()
+}
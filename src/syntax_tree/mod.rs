@@ -0,0 +1,51 @@
+mod imports;
+mod outline;
+mod symbols;
+
+pub use imports::{expand_use, imports, Import};
+pub use outline::outline;
+pub use symbols::{Symbol, SymbolIndex};
+
+use tree_sitter::{Language, Parser, Tree};
+
+// Tree-sitter grammar for a language name as returned by languages::detect_language
+pub fn grammar(language: &str) -> Option<Language> {
+    let grammar = match language {
+        "rust" => tree_sitter_rust::LANGUAGE,
+        "python" => tree_sitter_python::LANGUAGE,
+        "javascript" | "jsx" => tree_sitter_javascript::LANGUAGE,
+        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX,
+        "go" => tree_sitter_go::LANGUAGE,
+        "java" => tree_sitter_java::LANGUAGE,
+        _ => return None,
+    };
+    Some(grammar.into())
+}
+
+// Parses `content` with the grammar for `language`. Syntax errors still give a tree, with
+// error nodes where the grammar couldn't make sense of the input
+pub fn parse(content: &str, language: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&grammar(language)?).ok()?;
+    parser.parse(content, None)
+}
+
+// Where the line holding `position` starts, if only indentation comes before it on that line
+fn line_start(content: &str, position: usize) -> usize {
+    let start = content[..position]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    if content[start..position].trim().is_empty() {
+        start
+    } else {
+        position
+    }
+}
+
+// Whether an empty line separates `end` from `start`. Line comments can end with their newline
+fn blank_line_between(content: &str, end: usize, start: usize) -> bool {
+    let newlines = content[end..start].matches('\n').count();
+    let ended_line = content[..end].ends_with('\n');
+    newlines + usize::from(ended_line) >= 2
+}
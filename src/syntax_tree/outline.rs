@@ -0,0 +1,272 @@
+use std::ops::Range;
+use tree_sitter::Node;
+
+use super::{blank_line_between, line_start, parse};
+
+// Which nodes make up a language's outline
+struct OutlineRules {
+    // Top-level declarations that are kept, with nested function bodies elided
+    items: &'static [&'static str],
+    // Nodes kept when the declaration in their field is an item, such as `export ...`
+    wrappers: &'static [(&'static str, &'static str)],
+    // Variable declarations kept when they bind a function, such as `const f = () => {}`
+    bindings: &'static [&'static str],
+    // Nodes whose `body` field is elided when it's one of `blocks`
+    functions: &'static [&'static str],
+    blocks: &'static [&'static str],
+    // Comments and attributes directly above an item stay with it
+    attached: &'static [&'static str],
+    // Python keeps module and function docstrings, and elides to `...` instead of `{ ... }`
+    docstrings: bool,
+}
+
+const RUST: OutlineRules = OutlineRules {
+    items: &[
+        "function_item",
+        "function_signature_item",
+        "struct_item",
+        "enum_item",
+        "union_item",
+        "type_item",
+        "const_item",
+        "static_item",
+        "trait_item",
+        "impl_item",
+        "mod_item",
+        "foreign_mod_item",
+    ],
+    wrappers: &[],
+    bindings: &[],
+    functions: &["function_item"],
+    blocks: &["block"],
+    attached: &["line_comment", "block_comment", "attribute_item"],
+    docstrings: false,
+};
+
+const PYTHON: OutlineRules = OutlineRules {
+    items: &[
+        "function_definition",
+        "class_definition",
+        "decorated_definition",
+    ],
+    wrappers: &[],
+    bindings: &[],
+    functions: &["function_definition"],
+    blocks: &["block"],
+    attached: &["comment"],
+    docstrings: true,
+};
+
+const JAVASCRIPT: OutlineRules = OutlineRules {
+    items: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "interface_declaration",
+        "type_alias_declaration",
+        "enum_declaration",
+        "function_signature",
+        "internal_module",
+        "module",
+        "ambient_declaration",
+    ],
+    wrappers: &[("export_statement", "declaration")],
+    bindings: &["lexical_declaration", "variable_declaration"],
+    functions: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "function_expression",
+        "generator_function",
+        "arrow_function",
+        "method_definition",
+    ],
+    blocks: &["statement_block"],
+    attached: &["comment"],
+    docstrings: false,
+};
+
+const GO: OutlineRules = OutlineRules {
+    items: &[
+        "package_clause",
+        "function_declaration",
+        "method_declaration",
+        "type_declaration",
+        "const_declaration",
+    ],
+    wrappers: &[],
+    bindings: &[],
+    functions: &["function_declaration", "method_declaration", "func_literal"],
+    blocks: &["block"],
+    attached: &["comment"],
+    docstrings: false,
+};
+
+const JAVA: OutlineRules = OutlineRules {
+    items: &[
+        "package_declaration",
+        "class_declaration",
+        "interface_declaration",
+        "enum_declaration",
+        "record_declaration",
+        "annotation_type_declaration",
+    ],
+    wrappers: &[],
+    bindings: &[],
+    functions: &["method_declaration", "constructor_declaration"],
+    blocks: &["block", "constructor_body"],
+    attached: &["line_comment", "block_comment"],
+    docstrings: false,
+};
+
+fn rules(language: &str) -> Option<&'static OutlineRules> {
+    match language {
+        "rust" => Some(&RUST),
+        "python" => Some(&PYTHON),
+        "javascript" | "jsx" | "typescript" | "tsx" => Some(&JAVASCRIPT),
+        "go" => Some(&GO),
+        "java" => Some(&JAVA),
+        _ => None,
+    }
+}
+
+// Reduces a file to its declarations: types, signatures and the comments above them, with
+// function bodies replaced by `{ ... }`. None for languages without a grammar, or when the
+// file declares nothing, so callers fall back to the full content
+pub fn outline(content: &str, language: &str) -> Option<String> {
+    let rules = rules(language)?;
+    let tree = parse(content, language)?;
+    let root = tree.root_node();
+
+    let mut output = String::new();
+    let mut previous_end = None;
+    // Start and end of the run of comments and attributes seen since the last item
+    let mut attached: Option<Range<usize>> = None;
+    // A module docstring may follow a shebang, an encoding line or a license comment
+    let mut first_statement = true;
+
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        if rules.attached.contains(&node.kind()) {
+            attached = match attached {
+                Some(run) if !blank_line_between(content, run.end, node.start_byte()) => {
+                    Some(run.start..node.end_byte())
+                }
+                _ => Some(node.start_byte()..node.end_byte()),
+            };
+            continue;
+        }
+
+        let is_docstring = rules.docstrings && first_statement && is_docstring(node);
+        first_statement = false;
+        if is_docstring || is_item(node, rules) {
+            let start = match attached {
+                Some(run) if !blank_line_between(content, run.end, node.start_byte()) => run.start,
+                _ => node.start_byte(),
+            };
+            if let Some(end) = previous_end {
+                if blank_line_between(content, end, start) {
+                    output.push('\n');
+                }
+            }
+
+            let mut elisions = Vec::new();
+            collect_elisions(node, content, rules, &mut elisions);
+            output.push_str(&apply_elisions(
+                content,
+                line_start(content, start)..node.end_byte(),
+                &elisions,
+            ));
+            output.push('\n');
+            previous_end = Some(node.end_byte());
+        }
+        attached = None;
+    }
+
+    previous_end.map(|_| output)
+}
+
+fn is_item(node: Node, rules: &OutlineRules) -> bool {
+    let kind = node.kind();
+    if rules.items.contains(&kind) {
+        return true;
+    }
+    if let Some((_, field)) = rules.wrappers.iter().find(|(wrapper, _)| *wrapper == kind) {
+        return node
+            .child_by_field_name(field)
+            .is_some_and(|declaration| is_item(declaration, rules));
+    }
+    if rules.bindings.contains(&kind) {
+        let mut cursor = node.walk();
+        return node.named_children(&mut cursor).any(|declarator| {
+            declarator
+                .child_by_field_name("value")
+                .is_some_and(|value| rules.functions.contains(&value.kind()))
+        });
+    }
+    false
+}
+
+// A string on its own as the first statement of a module or body
+fn is_docstring(node: Node) -> bool {
+    node.kind() == "expression_statement"
+        && node
+            .named_child(0)
+            .is_some_and(|child| child.kind() == "string")
+}
+
+// Function bodies below `node`, with their replacements. Anything nested in a body goes with it
+fn collect_elisions(
+    node: Node,
+    content: &str,
+    rules: &OutlineRules,
+    elisions: &mut Vec<(Range<usize>, String)>,
+) {
+    if rules.functions.contains(&node.kind()) {
+        if let Some(body) = node
+            .child_by_field_name("body")
+            .filter(|body| rules.blocks.contains(&body.kind()))
+        {
+            elisions.push((body.byte_range(), elided_body(body, content, rules)));
+            return;
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_elisions(child, content, rules, elisions);
+    }
+}
+
+fn elided_body(body: Node, content: &str, rules: &OutlineRules) -> String {
+    if !rules.docstrings {
+        return "{ ... }".to_string();
+    }
+    // An indented Python block starts at its first statement, so `...` lines up with it
+    let docstring = body.named_child(0).filter(|first| is_docstring(*first));
+    let line = content[..body.start_byte()]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let indent = &content[line..body.start_byte()];
+    match docstring {
+        Some(docstring) if indent.trim().is_empty() => {
+            format!("{}\n{}...", &content[docstring.byte_range()], indent)
+        }
+        _ => "...".to_string(),
+    }
+}
+
+fn apply_elisions(
+    content: &str,
+    range: Range<usize>,
+    elisions: &[(Range<usize>, String)],
+) -> String {
+    let mut result = String::with_capacity(range.len());
+    let mut position = range.start;
+    for (elided, replacement) in elisions {
+        result.push_str(&content[position..elided.start]);
+        result.push_str(replacement);
+        position = elided.end;
+    }
+    result.push_str(&content[position..range.end]);
+    result
+}
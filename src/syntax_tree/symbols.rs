@@ -0,0 +1,665 @@
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use tree_sitter::Node;
+
+use super::{blank_line_between, line_start, parse};
+
+// A reference with more candidate definitions than this, none of them in the same file or
+// under the named type, is too ambiguous to follow
+const MAX_CANDIDATES: usize = 3;
+
+// Qualifiers that name the current type or module rather than another one
+const SELF_QUALIFIERS: &[&str] = &["self", "Self", "this", "cls", "super", "crate"];
+
+// Names used in code that may refer to a definition
+const REFERENCE_KINDS: &[&str] = &[
+    "identifier",
+    "type_identifier",
+    "field_identifier",
+    "property_identifier",
+];
+
+// Where names are declared rather than used: a field of a node, or "" for all of its children.
+// Identifiers below these, such as struct fields, parameters and pattern bindings, are not
+// references, except inside paths and the fields in USE_FIELDS
+const BINDING_FIELDS: &[(&str, &str)] = &[
+    // Rust
+    ("field_declaration", "name"),
+    ("enum_variant", "name"),
+    ("parameters", ""),
+    ("closure_parameters", ""),
+    ("parameter", "pattern"),
+    ("let_declaration", "pattern"),
+    ("let_condition", "pattern"),
+    ("for_expression", "pattern"),
+    ("match_arm", "pattern"),
+    ("field_initializer", "field"),
+    ("field_pattern", "name"),
+    ("shorthand_field_initializer", ""),
+    // Python
+    ("lambda_parameters", ""),
+    ("typed_parameter", ""),
+    ("default_parameter", "name"),
+    ("typed_default_parameter", "name"),
+    ("keyword_argument", "name"),
+    ("for_statement", "left"),
+    ("assignment", "left"),
+    ("augmented_assignment", "left"),
+    // JavaScript and TypeScript
+    ("formal_parameters", ""),
+    ("arrow_function", "parameter"),
+    ("catch_clause", "parameter"),
+    ("for_in_statement", "left"),
+    ("variable_declarator", "name"),
+    ("pair", "key"),
+    ("field_definition", "property"),
+    ("public_field_definition", "name"),
+    ("property_signature", "name"),
+    ("method_signature", "name"),
+    // Go
+    ("parameter_declaration", "name"),
+    ("short_var_declaration", "left"),
+    ("var_spec", "name"),
+    ("const_spec", "name"),
+    ("range_clause", "left"),
+    // Java
+    ("formal_parameter", "name"),
+    ("catch_formal_parameter", "name"),
+    ("enhanced_for_statement", "name"),
+    ("lambda_expression", "parameters"),
+];
+
+// Fields inside a binding that hold types or expressions again, like a parameter's type
+const USE_FIELDS: &[&str] = &["type", "return_type", "value", "default", "right", "body"];
+
+// Paths inside patterns, like `Kind::Empty` in a match arm, name definitions
+const PATH_KINDS: &[&str] = &["scoped_identifier", "scoped_type_identifier"];
+
+// Nodes such as `a::b` or `a.b`, with the field holding the `a` that qualifies the name
+const QUALIFIED_KINDS: &[(&str, &str)] = &[
+    ("scoped_identifier", "path"),
+    ("scoped_type_identifier", "path"),
+    ("field_expression", "value"),
+    ("member_expression", "object"),
+    ("attribute", "object"),
+    ("selector_expression", "operand"),
+    ("qualified_type", "package"),
+    ("method_invocation", "object"),
+    ("field_access", "object"),
+];
+
+// How a language declares things
+struct SymbolRules {
+    // Definitions with a `name` field
+    definitions: &'static [&'static str],
+    // Definitions whose members are qualified by their name, such as classes and traits
+    scopes: &'static [&'static str],
+    // Blocks that qualify their members by the name in a field without being definitions
+    // themselves, such as Rust impls and modules
+    namespaces: &'static [(&'static str, &'static str)],
+    // Declarators that define a function when their value is one of `functions`
+    bindings: &'static [&'static str],
+    functions: &'static [&'static str],
+    // Parents whose text belongs to the definition, such as decorators and `export`
+    wrappers: &'static [&'static str],
+    // Comments and attributes directly above a definition
+    attached: &'static [&'static str],
+    // Go methods are qualified by their receiver's type
+    receivers: bool,
+}
+
+const RUST: SymbolRules = SymbolRules {
+    definitions: &[
+        "function_item",
+        "function_signature_item",
+        "struct_item",
+        "enum_item",
+        "union_item",
+        "trait_item",
+        "type_item",
+        "const_item",
+        "static_item",
+        "macro_definition",
+    ],
+    scopes: &["trait_item"],
+    namespaces: &[("impl_item", "type"), ("mod_item", "name")],
+    bindings: &[],
+    functions: &[],
+    wrappers: &[],
+    attached: &["line_comment", "block_comment", "attribute_item"],
+    receivers: false,
+};
+
+const PYTHON: SymbolRules = SymbolRules {
+    definitions: &["function_definition", "class_definition"],
+    scopes: &["class_definition"],
+    namespaces: &[],
+    bindings: &[],
+    functions: &[],
+    wrappers: &["decorated_definition"],
+    attached: &["comment"],
+    receivers: false,
+};
+
+const JAVASCRIPT: SymbolRules = SymbolRules {
+    definitions: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "method_definition",
+        "interface_declaration",
+        "type_alias_declaration",
+        "enum_declaration",
+    ],
+    scopes: &["class_declaration", "abstract_class_declaration"],
+    namespaces: &[],
+    bindings: &["variable_declarator"],
+    functions: &[
+        "arrow_function",
+        "function_expression",
+        "generator_function",
+    ],
+    wrappers: &[
+        "export_statement",
+        "lexical_declaration",
+        "variable_declaration",
+    ],
+    attached: &["comment"],
+    receivers: false,
+};
+
+const GO: SymbolRules = SymbolRules {
+    definitions: &["function_declaration", "method_declaration", "type_spec"],
+    scopes: &[],
+    namespaces: &[],
+    bindings: &[],
+    functions: &[],
+    wrappers: &["type_declaration"],
+    attached: &["comment"],
+    receivers: true,
+};
+
+const JAVA: SymbolRules = SymbolRules {
+    definitions: &[
+        "class_declaration",
+        "interface_declaration",
+        "enum_declaration",
+        "record_declaration",
+        "method_declaration",
+        "constructor_declaration",
+    ],
+    scopes: &[
+        "class_declaration",
+        "interface_declaration",
+        "enum_declaration",
+        "record_declaration",
+    ],
+    namespaces: &[],
+    bindings: &[],
+    functions: &[],
+    wrappers: &[],
+    attached: &["line_comment", "block_comment"],
+    receivers: false,
+};
+
+fn rules(language: &str) -> Option<&'static SymbolRules> {
+    match language {
+        "rust" => Some(&RUST),
+        "python" => Some(&PYTHON),
+        "javascript" | "jsx" | "typescript" | "tsx" => Some(&JAVASCRIPT),
+        "go" => Some(&GO),
+        "java" => Some(&JAVA),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Reference {
+    // `Writer` in `Writer::new`, `client` in `client.get()`
+    qualifier: Option<String>,
+    name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    // Module path, enclosing types and the name, e.g. ["crate", "llm", "Client", "send"]
+    pub qualified: Vec<String>,
+    pub path: PathBuf,
+    // 1-based and inclusive, counting the comments and attributes above the definition
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+    range: Range<usize>,
+    references: Vec<Reference>,
+}
+
+impl Symbol {
+    fn contains(&self, other: &Symbol) -> bool {
+        self.path == other.path
+            && self.range.start <= other.range.start
+            && other.range.end <= self.range.end
+    }
+}
+
+// Every definition in a set of files, looked up by name
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    symbols: Vec<Symbol>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl SymbolIndex {
+    // Indexes (path, content, language) triples; files in other languages are skipped
+    pub fn build(files: &[(PathBuf, String, &str)]) -> Self {
+        let symbols: Vec<Symbol> = files
+            .par_iter()
+            .flat_map_iter(|(path, content, language)| file_symbols(path, content, language))
+            .collect();
+
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, symbol) in symbols.iter().enumerate() {
+            by_name.entry(symbol.name.clone()).or_default().push(index);
+        }
+        Self { symbols, by_name }
+    }
+
+    // Definitions matching `crate::llm::summarize`, `Client.get` or a bare name. Qualified
+    // queries match the end of the qualified name, or failing that any definition whose path
+    // contains the query's segments in order, which covers re-exports
+    pub fn find(&self, query: &str) -> Vec<&Symbol> {
+        self.matching(query)
+            .into_iter()
+            .map(|index| &self.symbols[index])
+            .collect()
+    }
+
+    fn matching(&self, query: &str) -> Vec<usize> {
+        let segments: Vec<&str> = query
+            .split([':', '.', '/'])
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let Some(name) = segments.last() else {
+            return Vec::new();
+        };
+        let candidates = self.by_name.get(*name).cloned().unwrap_or_default();
+
+        let exact: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|&index| ends_with(&self.symbols[index].qualified, &segments))
+            .collect();
+        if !exact.is_empty() {
+            return exact;
+        }
+        candidates
+            .into_iter()
+            .filter(|&index| is_subsequence(&segments, &self.symbols[index].qualified))
+            .collect()
+    }
+
+    // The definitions matching `queries` and those they reference, following references up
+    // to `depth` levels. Definitions nested in another selected one are left out
+    pub fn select(&self, queries: &[String], depth: usize) -> Result<Vec<&Symbol>> {
+        let mut selected = Vec::new();
+        let mut seen = HashSet::new();
+        for query in queries {
+            let matches = self.matching(query);
+            if matches.is_empty() {
+                return Err(anyhow!("No definition matches --symbol {}", query));
+            }
+            selected.extend(matches.into_iter().filter(|&index| seen.insert(index)));
+        }
+
+        let mut frontier = selected.clone();
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for &index in &frontier {
+                for reference in &self.symbols[index].references {
+                    next.extend(
+                        self.resolve(index, reference)
+                            .into_iter()
+                            .filter(|&target| seen.insert(target)),
+                    );
+                }
+            }
+            selected.extend(&next);
+            frontier = next;
+        }
+
+        Ok(selected
+            .iter()
+            .map(|&index| &self.symbols[index])
+            .filter(|symbol| {
+                !selected.iter().any(|&other| {
+                    let other = &self.symbols[other];
+                    !std::ptr::eq(other, *symbol) && other.contains(symbol)
+                })
+            })
+            .collect())
+    }
+
+    // Definitions a reference may point to: under the named type or module, else in the same
+    // file, else anywhere as long as there are only a few. Names qualified by something else,
+    // usually a variable as in `items.iter()`, aren't looked for in other files
+    fn resolve(&self, from: usize, reference: &Reference) -> Vec<usize> {
+        let source = &self.symbols[from];
+        let candidates: Vec<usize> = self
+            .by_name
+            .get(&reference.name)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&index| {
+                let symbol = &self.symbols[index];
+                !symbol.contains(source) && !source.contains(symbol)
+            })
+            .collect();
+
+        if let Some(qualifier) = &reference.qualifier {
+            let qualified: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&index| {
+                    let segments = &self.symbols[index].qualified;
+                    segments.len() >= 2 && &segments[segments.len() - 2] == qualifier
+                })
+                .collect();
+            if !qualified.is_empty() {
+                return qualified;
+            }
+        }
+
+        let local: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|&index| self.symbols[index].path == source.path)
+            .collect();
+        if !local.is_empty() {
+            return local;
+        }
+        if reference.qualifier.is_none() && candidates.len() <= MAX_CANDIDATES {
+            candidates
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn ends_with(qualified: &[String], segments: &[&str]) -> bool {
+    qualified.len() >= segments.len()
+        && qualified[qualified.len() - segments.len()..]
+            .iter()
+            .zip(segments)
+            .all(|(have, want)| have == want)
+}
+
+// Whether `segments` appear in order within `qualified`, ending with its last segment
+fn is_subsequence(segments: &[&str], qualified: &[String]) -> bool {
+    if segments.last().copied() != qualified.last().map(String::as_str) {
+        return false;
+    }
+    let mut remaining = qualified.iter();
+    segments
+        .iter()
+        .all(|segment| remaining.any(|have| have == segment))
+}
+
+// The module path a file's definitions live under, from its location
+fn module_path(path: &Path, language: &str) -> Vec<String> {
+    let mut segments: Vec<String> = path
+        .with_extension("")
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    match language {
+        "rust" => {
+            // Paths below src/ map onto the crate's modules
+            if let Some(src) = segments.iter().rposition(|segment| segment == "src") {
+                segments.drain(..=src);
+                segments.insert(0, "crate".to_string());
+            }
+            if matches!(
+                segments.last().map(String::as_str),
+                Some("lib" | "main" | "mod")
+            ) {
+                segments.pop();
+            }
+        }
+        "python" if segments.last().is_some_and(|last| last == "__init__") => {
+            segments.pop();
+        }
+        _ => {}
+    }
+    segments
+}
+
+fn file_symbols(path: &Path, content: &str, language: &str) -> Vec<Symbol> {
+    let (Some(rules), Some(tree)) = (rules(language), parse(content, language)) else {
+        return Vec::new();
+    };
+    let mut symbols = Vec::new();
+    let mut scope = module_path(path, language);
+    collect_symbols(
+        tree.root_node(),
+        content,
+        path,
+        rules,
+        &mut scope,
+        &mut symbols,
+    );
+    symbols
+}
+
+fn collect_symbols(
+    node: Node,
+    content: &str,
+    path: &Path,
+    rules: &SymbolRules,
+    scope: &mut Vec<String>,
+    symbols: &mut Vec<Symbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let kind = child.kind();
+
+        if let Some((_, field)) = rules
+            .namespaces
+            .iter()
+            .find(|(namespace, _)| *namespace == kind)
+        {
+            let name = child
+                .child_by_field_name(field)
+                .and_then(|node| match node.kind() {
+                    "identifier" => Some(content[node.byte_range()].to_string()),
+                    _ => type_name(node, content),
+                });
+            if let Some(name) = name {
+                scope.push(name);
+                collect_symbols(child, content, path, rules, scope, symbols);
+                scope.pop();
+            }
+            continue;
+        }
+
+        let Some(name) = definition_name(child, content, rules) else {
+            collect_symbols(child, content, path, rules, scope, symbols);
+            continue;
+        };
+
+        let receiver = rules
+            .receivers
+            .then(|| child.child_by_field_name("receiver"))
+            .flatten()
+            .and_then(|receiver| type_name(receiver, content));
+        let mut qualified = scope.clone();
+        qualified.extend(receiver);
+        qualified.push(name.clone());
+        symbols.push(symbol(child, content, path, rules, name.clone(), qualified));
+
+        if rules.scopes.contains(&kind) {
+            scope.push(name);
+            collect_symbols(child, content, path, rules, scope, symbols);
+            scope.pop();
+        }
+    }
+}
+
+fn definition_name(node: Node, content: &str, rules: &SymbolRules) -> Option<String> {
+    let kind = node.kind();
+    let is_definition = rules.definitions.contains(&kind)
+        || (rules.bindings.contains(&kind)
+            && node
+                .child_by_field_name("value")
+                .is_some_and(|value| rules.functions.contains(&value.kind())));
+    if !is_definition {
+        return None;
+    }
+    let name = node.child_by_field_name("name")?;
+    REFERENCE_KINDS
+        .contains(&name.kind())
+        .then(|| content[name.byte_range()].to_string())
+}
+
+// The plain name of a type such as `Foo`, `Foo<T>`, `a::Foo` or a Go receiver `(f *Foo)`
+fn type_name(node: Node, content: &str) -> Option<String> {
+    if node.kind() == "type_identifier" {
+        return Some(content[node.byte_range()].to_string());
+    }
+    let fields = ["name", "type"]
+        .into_iter()
+        .filter_map(|field| node.child_by_field_name(field));
+    if let Some(name) = fields
+        .into_iter()
+        .find_map(|inner| type_name(inner, content))
+    {
+        return Some(name);
+    }
+    let mut cursor = node.walk();
+    let name = node
+        .named_children(&mut cursor)
+        .find_map(|child| type_name(child, content));
+    name
+}
+
+fn symbol(
+    node: Node,
+    content: &str,
+    path: &Path,
+    rules: &SymbolRules,
+    name: String,
+    qualified: Vec<String>,
+) -> Symbol {
+    // Decorators, `export` and `const f = ...` belong to the definition
+    let mut outer = node;
+    while let Some(parent) = outer
+        .parent()
+        .filter(|parent| rules.wrappers.contains(&parent.kind()))
+    {
+        outer = parent;
+    }
+
+    // So do the comments and attributes right above it
+    let mut start = outer.start_byte();
+    let mut previous = outer.prev_named_sibling();
+    while let Some(sibling) = previous {
+        if !rules.attached.contains(&sibling.kind())
+            || blank_line_between(content, sibling.end_byte(), start)
+        {
+            break;
+        }
+        start = sibling.start_byte();
+        previous = sibling.prev_named_sibling();
+    }
+    let start = line_start(content, start);
+    let end = outer.end_byte();
+
+    // In the order they appear, so the selection comes out the same every time. Names bound
+    // inside the definition, like parameters and locals, shadow definitions elsewhere
+    let mut references = Vec::new();
+    let mut bindings = HashSet::new();
+    collect_references(node, content, false, &mut references, &mut bindings);
+    references
+        .retain(|reference| reference.qualifier.is_some() || !bindings.contains(&reference.name));
+
+    Symbol {
+        name,
+        qualified,
+        path: path.to_path_buf(),
+        start_line: content[..start].matches('\n').count() + 1,
+        end_line: content[..end].trim_end_matches('\n').matches('\n').count() + 1,
+        text: content[start..end].to_string(),
+        range: start..end,
+        references,
+    }
+}
+
+fn collect_references(
+    node: Node,
+    content: &str,
+    in_binding: bool,
+    references: &mut Vec<Reference>,
+    bindings: &mut HashSet<String>,
+) {
+    if REFERENCE_KINDS.contains(&node.kind()) {
+        if in_binding {
+            bindings.insert(content[node.byte_range()].to_string());
+            return;
+        }
+        let reference = Reference {
+            qualifier: qualifier(node, content),
+            name: content[node.byte_range()].to_string(),
+        };
+        if !references.contains(&reference) {
+            references.push(reference);
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    if !cursor.goto_first_child() {
+        return;
+    }
+    loop {
+        let child = cursor.node();
+        if child.is_named() {
+            let field = cursor.field_name();
+            // Macro arguments are plain tokens, where `name:` is usually a struct field too
+            let declares = BINDING_FIELDS.iter().any(|&(kind, binding)| {
+                kind == node.kind() && (binding.is_empty() || Some(binding) == field)
+            }) || (node.kind() == "token_tree"
+                && child.next_sibling().is_some_and(|next| next.kind() == ":"));
+            let uses = field.is_some_and(|field| USE_FIELDS.contains(&field))
+                || PATH_KINDS.contains(&child.kind());
+            let in_binding = (in_binding || declares) && !uses;
+            collect_references(child, content, in_binding, references, bindings);
+        }
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+// The last name in the `a::b` or `a.b` that qualifies `node`, unless it means "this one"
+fn qualifier(node: Node, content: &str) -> Option<String> {
+    let parent = node.parent()?;
+    let (_, field) = QUALIFIED_KINDS
+        .iter()
+        .find(|(kind, _)| *kind == parent.kind())?;
+    let qualifier = parent
+        .child_by_field_name(field)
+        .filter(|qualifier| qualifier.id() != node.id())?;
+    let text = &content[qualifier.byte_range()];
+    let last = text
+        .rsplit(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+        .find(|segment| !segment.is_empty())?;
+    (!SELF_QUALIFIERS.contains(&last)).then(|| last.to_string())
+}
@@ -1,5 +1,17 @@
+mod pricing;
+
+pub use pricing::{load_pricing, model_key, ModelPricing, PriceTier, PricingTable};
+
 use crate::models::TokenizerModel;
-use tiktoken_rs::{cl100k_base, p50k_base};
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use tiktoken_rs::{cl100k_base, p50k_base, CoreBPE};
+
+// Building a BPE is expensive, so each encoding is loaded once and shared
+lazy_static! {
+    static ref CL100K_BASE: CoreBPE = cl100k_base().unwrap();
+    static ref P50K_BASE: CoreBPE = p50k_base().unwrap();
+}
 
 pub fn get_tokenizer_name(model: &TokenizerModel) -> &'static str {
     match model {
@@ -19,41 +31,76 @@ pub fn get_tokenizer_name(model: &TokenizerModel) -> &'static str {
     }
 }
 
-pub fn get_token_cost(model: &TokenizerModel, _tokens: usize) -> (f64, f64) {
-    // (input_cost_per_1k, output_cost_per_1k)
+pub fn get_token_cost(model: &TokenizerModel, tokens: usize, pricing: &PricingTable) -> (f64, f64) {
+    // (input_cost_per_1k, output_cost_per_1k) for the tier matching the prompt size
+    pricing
+        .get(model)
+        .and_then(|pricing| pricing.tier_for(tokens))
+        .map_or((0.0, 0.0), |tier| {
+            (tier.input / 1000.0, tier.output / 1000.0)
+        })
+}
+
+pub fn get_cached_input_cost(
+    model: &TokenizerModel,
+    tokens: usize,
+    pricing: &PricingTable,
+) -> Option<f64> {
+    // Cached-input cost per 1k tokens, when the provider offers context caching
+    pricing
+        .get(model)
+        .and_then(|pricing| pricing.tier_for(tokens))
+        .and_then(|tier| tier.cached_input)
+        .map(|cost| cost / 1000.0)
+}
+
+pub fn get_batch_discount(model: &TokenizerModel, pricing: &PricingTable) -> Option<f64> {
+    pricing
+        .get(model)
+        .and_then(|pricing| pricing.batch_discount)
+}
+
+pub fn get_context_window(model: &TokenizerModel) -> usize {
+    // Maximum input tokens accepted by the model
     match model {
-        TokenizerModel::Gemini15Pro => (0.0000, 0.0000), // Estimated
-        TokenizerModel::Gemini15Flash => (0.0000, 0.0000), // Estimated
-        TokenizerModel::Gemini20Flash => (0.0000, 0.0000), // Currently free during preview
-        TokenizerModel::Gemini20FlashLite => (0.0000, 0.0000), // Currently free during preview
-        TokenizerModel::Gemini20Pro => (0.0000, 0.0000), // Currently free during preview
-        TokenizerModel::Gemini20ProExp => (0.0000, 0.0000), // Currently free during preview (experimental)
-        TokenizerModel::Gemini20ProExp0205 => (0.0000, 0.0000), // Currently free during preview (experimental)
-        TokenizerModel::Gemini20FlashThinkingExp => (0.0000, 0.0000), // Currently free during preview (experimental)
-        TokenizerModel::Gpt35Turbo => (0.0010, 0.0020), // $0.0010 per 1k input, $0.0020 per 1k output
-        TokenizerModel::Gpt4 => (0.03, 0.06),           // $0.03 per 1k input, $0.06 per 1k output
-        TokenizerModel::Gpt4Turbo => (0.01, 0.03),      // $0.01 per 1k input, $0.03 per 1k output
-        TokenizerModel::Claude3Sonnet => (0.003, 0.015), // $0.003 per 1k input, $0.015 per 1k output
-        TokenizerModel::Claude3Opus => (0.015, 0.075), // $0.015 per 1k input, $0.075 per 1k output
+        TokenizerModel::Gemini15Pro => 2_097_152,
+        TokenizerModel::Gemini15Flash => 1_048_576,
+        TokenizerModel::Gemini20Flash => 1_048_576,
+        TokenizerModel::Gemini20FlashLite => 1_048_576,
+        TokenizerModel::Gemini20Pro => 2_097_152,
+        TokenizerModel::Gemini20ProExp => 2_097_152,
+        TokenizerModel::Gemini20ProExp0205 => 2_097_152,
+        TokenizerModel::Gemini20FlashThinkingExp => 1_048_576,
+        TokenizerModel::Gpt35Turbo => 16_385,
+        TokenizerModel::Gpt4 => 8_192,
+        TokenizerModel::Gpt4Turbo => 128_000,
+        TokenizerModel::Claude3Sonnet => 200_000,
+        TokenizerModel::Claude3Opus => 200_000,
     }
 }
 
 pub fn count_tokens(text: &str, model: &TokenizerModel) -> usize {
     // Currently we're using tiktoken for all models but in a real-world implementation
     // we'd use different tokenizers for each model family
-    match get_tokenizer_name(model) {
-        "cl100k_base" => {
-            let bpe = cl100k_base().unwrap();
-            bpe.encode_ordinary(text).len()
-        }
-        "p50k_base" => {
-            let bpe = p50k_base().unwrap();
-            bpe.encode_ordinary(text).len()
-        }
-        _ => {
-            // Fallback to p50k_base
-            let bpe = p50k_base().unwrap();
-            bpe.encode_ordinary(text).len()
-        }
+    count_tokens_with(text, get_tokenizer_name(model))
+}
+
+// Counts with a tokenizer named as get_tokenizer_name returns it
+pub fn count_tokens_with(text: &str, tokenizer: &str) -> usize {
+    match tokenizer {
+        "cl100k_base" => CL100K_BASE.encode_ordinary(text).len(),
+        // Fallback to p50k_base
+        _ => P50K_BASE.encode_ordinary(text).len(),
     }
 }
+
+// Every tokenizer some model uses, each once
+pub fn tokenizer_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = TokenizerModel::value_variants()
+        .iter()
+        .map(get_tokenizer_name)
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::models::TokenizerModel;
+
+// Default prices shipped with the binary, in USD per 1M tokens
+const DEFAULT_PRICING: &str = include_str!("pricing.json");
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceTier {
+    // Prompts up to this many tokens use this tier; `None` means no upper bound
+    #[serde(default)]
+    pub max_prompt_tokens: Option<usize>,
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub tiers: Vec<PriceTier>,
+    // Fraction taken off the regular price for batch requests, e.g. 0.5 for 50% off
+    #[serde(default)]
+    pub batch_discount: Option<f64>,
+}
+
+impl ModelPricing {
+    pub fn tier_for(&self, prompt_tokens: usize) -> Option<&PriceTier> {
+        self.tiers
+            .iter()
+            .find(|tier| {
+                tier.max_prompt_tokens
+                    .is_none_or(|max| prompt_tokens <= max)
+            })
+            .or_else(|| self.tiers.last())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingTable {
+    pub models: HashMap<String, ModelPricing>,
+}
+
+// The built-in prices
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self::default_table()
+    }
+}
+
+impl PricingTable {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let table: Self = serde_json::from_str(json)?;
+        for (name, pricing) in &table.models {
+            if pricing.tiers.is_empty() {
+                return Err(anyhow!("Pricing for '{}' has no tiers", name));
+            }
+        }
+        Ok(table)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pricing file {}", path.display()))?;
+        Self::from_json(&json)
+            .with_context(|| format!("Failed to parse pricing file {}", path.display()))
+    }
+
+    pub fn default_table() -> Self {
+        Self::from_json(DEFAULT_PRICING).expect("Embedded pricing table is invalid")
+    }
+
+    // Entries from `other` replace the pricing of the same model
+    pub fn merge(&mut self, other: PricingTable) {
+        self.models.extend(other.models);
+    }
+
+    pub fn get(&self, model: &TokenizerModel) -> Option<&ModelPricing> {
+        self.models.get(&model_key(model))
+    }
+}
+
+// Pricing entries are keyed by the model name accepted by `--model`
+pub fn model_key(model: &TokenizerModel) -> String {
+    model
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn user_pricing_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("summarize").join("pricing.json"))
+}
+
+// The built-in table with the --pricing file, or else the user's config-dir pricing.json,
+// merged over it. A --pricing file that can't be read or parsed is an error, while a bad
+// config-dir file only warns and leaves the built-in prices
+pub fn load_pricing(override_path: Option<&Path>) -> Result<PricingTable> {
+    let mut table = PricingTable::default_table();
+
+    if let Some(path) = override_path {
+        table.merge(PricingTable::load(path)?);
+    } else if let Some(path) = user_pricing_path().filter(|path| path.exists()) {
+        match PricingTable::load(&path) {
+            Ok(user) => table.merge(user),
+            Err(e) => {
+                eprintln!("Warning: {:#}", e);
+                eprintln!("Using built-in pricing");
+            }
+        }
+    }
+
+    Ok(table)
+}
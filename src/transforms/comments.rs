@@ -0,0 +1,295 @@
+use std::collections::HashSet;
+
+use super::syntax::{CommentSyntax, Quote, QuoteKind};
+
+// Words that mark a leading comment block as a license header
+const LICENSE_MARKERS: &[&str] = &["license", "copyright", "spdx-license-identifier"];
+
+// Length of the literal starting `rest`, up to and including its closing quote. Unterminated
+// literals run to the end of the line, or of the input if they may span lines
+fn literal_len(rest: &str, quote: &Quote) -> usize {
+    let mut index = quote.open.len();
+    while index < rest.len() {
+        let tail = &rest[index..];
+        if tail.starts_with(quote.close) {
+            return index + quote.close.len();
+        }
+        let Some(ch) = tail.chars().next() else {
+            break;
+        };
+        if ch == '\n' && !quote.multiline {
+            return index;
+        }
+        index += ch.len_utf8();
+        if ch == '\\' && quote.escapes {
+            index += tail[1..].chars().next().map_or(0, char::len_utf8);
+        }
+    }
+    rest.len()
+}
+
+// Length of a char literal such as 'a' or '\n', or None for a lifetime like 'a
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    if first == '\\' {
+        // Escapes such as '\u{1F600}' are short but variable in length
+        return rest[1..]
+            .char_indices()
+            .take(12)
+            .skip(2)
+            .find(|&(_, ch)| ch == '\'')
+            .map(|(index, _)| index + 2);
+    }
+    match chars.next() {
+        Some((index, '\'')) if first != '\'' && first != '\n' => Some(index + 1),
+        _ => None,
+    }
+}
+
+// Length of a Rust raw string such as r"..." or br#"..."#
+fn raw_string_len(rest: &str) -> Option<usize> {
+    let after_prefix = rest.strip_prefix('r')?;
+    let hashes = after_prefix.len() - after_prefix.trim_start_matches('#').len();
+    let body = after_prefix[hashes..].strip_prefix('"')?;
+    let close = format!("\"{}", "#".repeat(hashes));
+    let end = body
+        .find(&close)
+        .map_or(body.len(), |end| end + close.len());
+    Some(1 + hashes + 1 + end)
+}
+
+// Length of the block comment starting `rest`, honoring nesting where the language allows it
+fn block_len(rest: &str, open: &str, close: &str, nested: bool) -> usize {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < rest.len() {
+        let tail = &rest[index..];
+        if (depth == 0 || nested) && tail.starts_with(open) {
+            depth += 1;
+            index += open.len();
+        } else if tail.starts_with(close) {
+            depth -= 1;
+            index += close.len();
+            if depth == 0 {
+                return index;
+            }
+        } else {
+            index += tail.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    rest.len()
+}
+
+// Keywords after which a slash starts a regex rather than a division
+const REGEX_KEYWORDS: &[&str] = &[
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+// Whether a slash after `before` starts an operand, where only a regex literal can begin
+fn regex_allowed(before: &str) -> bool {
+    let before = before.trim_end();
+    match before.chars().next_back() {
+        None => true,
+        Some(ch) if "(,=:[!&|?{};+-*%~^".contains(ch) => true,
+        Some(ch) if ch.is_alphanumeric() || ch == '_' || ch == '$' => {
+            let word_start = before
+                .rfind(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$'))
+                .map_or(0, |index| index + 1);
+            REGEX_KEYWORDS.contains(&&before[word_start..])
+        }
+        _ => false,
+    }
+}
+
+// Length of a regex literal such as /[/"]+/g, including its flags. Slashes inside a character
+// class don't close it, and one that isn't closed on its line isn't a regex
+fn regex_literal_len(rest: &str) -> Option<usize> {
+    let mut in_class = false;
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return None,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => {
+                let flags = rest[index + 1..]
+                    .find(|ch: char| !ch.is_ascii_alphabetic())
+                    .unwrap_or(rest.len() - index - 1);
+                return Some(index + 1 + flags);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_word_start(before: &str) -> bool {
+    before
+        .chars()
+        .next_back()
+        .is_none_or(|ch| ch.is_whitespace())
+}
+
+// Removes comments while keeping string literals intact, so `"http://..."` or `'#fff'` survive.
+// Lines left empty by a removed comment are dropped, others lose their trailing whitespace
+pub fn strip_comments(content: &str, syntax: &CommentSyntax) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut stripped_lines = HashSet::new();
+    let mut line = 0;
+    let mut index = 0;
+
+    // A shebang looks like a comment in most scripting languages but has to stay
+    if content.starts_with("#!") {
+        index = content.find('\n').unwrap_or(content.len());
+        output.push_str(&content[..index]);
+    }
+
+    while index < content.len() {
+        let rest = &content[index..];
+        let previous = content[..index].chars().next_back();
+        let at_identifier = previous.is_some_and(|ch| ch.is_alphanumeric() || ch == '_');
+
+        // String literals are copied as they are
+        let literal = if syntax.raw_strings && !at_identifier {
+            raw_string_len(rest).or_else(|| {
+                rest.strip_prefix('b')
+                    .and_then(raw_string_len)
+                    .map(|len| len + 1)
+            })
+        } else {
+            None
+        };
+        let literal = literal.or_else(|| {
+            let regex = syntax.regex_literals
+                && rest.starts_with('/')
+                && !rest.starts_with("//")
+                && !rest.starts_with("/*")
+                && regex_allowed(&content[..index]);
+            if regex {
+                if let Some(len) = regex_literal_len(rest) {
+                    return Some(len);
+                }
+            }
+            let quote = syntax.quotes.iter().find(|q| rest.starts_with(q.open))?;
+            match quote.kind {
+                QuoteKind::String => Some(literal_len(rest, quote)),
+                // Not a char literal, so only the quote itself is copied
+                QuoteKind::Char => Some(char_literal_len(rest).unwrap_or(1)),
+            }
+        });
+        if let Some(len) = literal {
+            let text = &rest[..len];
+            line += text.matches('\n').count();
+            output.push_str(text);
+            index += len;
+            continue;
+        }
+
+        if let Some((open, close)) = syntax.block.iter().find(|(open, _)| rest.starts_with(open)) {
+            stripped_lines.insert(line);
+            index += block_len(rest, open, close, syntax.nested_blocks);
+            continue;
+        }
+
+        let line_comment = syntax.line.iter().any(|marker| rest.starts_with(marker))
+            && (!syntax.line_at_word_start || is_word_start(&content[..index]));
+        if line_comment {
+            stripped_lines.insert(line);
+            index += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+
+        let ch = rest.chars().next().unwrap_or_default();
+        if ch == '\n' {
+            line += 1;
+        }
+        output.push(ch);
+        index += ch.len_utf8();
+    }
+
+    let mut result = String::with_capacity(output.len());
+    for (number, text) in output.split_inclusive('\n').enumerate() {
+        if !stripped_lines.contains(&number) {
+            result.push_str(text);
+            continue;
+        }
+        let trimmed = text.trim_end();
+        if !trimmed.is_empty() {
+            result.push_str(trimmed);
+            if text.ends_with('\n') {
+                result.push('\n');
+            }
+        }
+    }
+    result
+}
+
+// Byte length of the comment block at the start of `content`, if there is one
+fn leading_comment_len(content: &str, syntax: &CommentSyntax) -> Option<usize> {
+    if let Some((open, close)) = syntax
+        .block
+        .iter()
+        .find(|(open, _)| content.starts_with(open))
+    {
+        return Some(block_len(content, open, close, syntax.nested_blocks));
+    }
+
+    let mut len = 0;
+    for text in content.split_inclusive('\n') {
+        let trimmed = text.trim_start();
+        if !syntax.line.iter().any(|marker| trimmed.starts_with(marker)) {
+            break;
+        }
+        len += text.len();
+    }
+    (len > 0).then_some(len)
+}
+
+// Drops a license or copyright comment at the top of the file, after any shebang line
+pub fn strip_license_header(content: &str, syntax: &CommentSyntax) -> String {
+    let shebang_len = if content.starts_with("#!") {
+        content.find('\n').map_or(content.len(), |end| end + 1)
+    } else {
+        0
+    };
+    let (shebang, body) = content.split_at(shebang_len);
+    let start = body.len() - body.trim_start().len();
+
+    let Some(len) = leading_comment_len(&body[start..], syntax) else {
+        return content.to_string();
+    };
+    let comment = body[start..start + len].to_lowercase();
+    if !LICENSE_MARKERS
+        .iter()
+        .any(|marker| comment.contains(marker))
+    {
+        return content.to_string();
+    }
+
+    // Blank lines after the header go too, but not the indentation of the first code line
+    let mut rest = &body[start + len..];
+    while let Some(end) = rest.find('\n') {
+        if !rest[..end].trim().is_empty() {
+            break;
+        }
+        rest = &rest[end + 1..];
+    }
+    format!("{}{}", shebang, rest)
+}
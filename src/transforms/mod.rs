@@ -0,0 +1,74 @@
+mod comments;
+mod syntax;
+
+pub use comments::{strip_comments, strip_license_header};
+pub use syntax::{CommentSyntax, Quote, QuoteKind};
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::languages::LanguageOverrides;
+use crate::models::Transform;
+use syntax::COMMENT_SYNTAX;
+
+lazy_static! {
+    static ref LANG_TO_SYNTAX: HashMap<&'static str, CommentSyntax> =
+        COMMENT_SYNTAX.iter().copied().collect();
+}
+
+pub fn comment_syntax(language: &str) -> Option<&'static CommentSyntax> {
+    LANG_TO_SYNTAX.get(language)
+}
+
+pub fn trim_trailing_whitespace(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| match line.strip_suffix('\n') {
+            Some(text) => format!("{}\n", text.trim_end()),
+            None => line.trim_end().to_string(),
+        })
+        .collect()
+}
+
+// Runs of blank lines become a single one, and blank lines at the start are dropped
+pub fn collapse_blank_lines(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut previous_blank = true;
+    for line in content.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if !(blank && previous_blank) {
+            result.push_str(line);
+        }
+        previous_blank = blank;
+    }
+    result
+}
+
+// Applies --strip in a fixed order: license header, comments, trailing whitespace, blank lines.
+// Comment-based steps are skipped for languages without known comment syntax
+pub fn apply_transforms(
+    content: &str,
+    path: &Path,
+    transforms: &[Transform],
+    languages: &LanguageOverrides,
+) -> String {
+    let syntax = languages
+        .detect(path, Some(content))
+        .and_then(comment_syntax);
+    let mut content = content.to_string();
+
+    if let (true, Some(syntax)) = (transforms.contains(&Transform::License), syntax) {
+        content = strip_license_header(&content, syntax);
+    }
+    if let (true, Some(syntax)) = (transforms.contains(&Transform::Comments), syntax) {
+        content = strip_comments(&content, syntax);
+    }
+    if transforms.contains(&Transform::TrailingWhitespace) {
+        content = trim_trailing_whitespace(&content);
+    }
+    if transforms.contains(&Transform::BlankLines) {
+        content = collapse_blank_lines(&content);
+    }
+    content
+}
@@ -0,0 +1,241 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteKind {
+    // Ends at the closing quote; a backslash escapes the next character when `escapes` is set
+    String,
+    // Like String, but only when it closes within one character or escape, as in 'a' or '\n';
+    // otherwise it's a Rust lifetime or label and the quote is left alone
+    Char,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub open: &'static str,
+    pub close: &'static str,
+    pub kind: QuoteKind,
+    pub escapes: bool,
+    // Whether the literal may span lines; unterminated single-line literals end at the newline
+    pub multiline: bool,
+}
+
+const fn quote(open: &'static str, close: &'static str, escapes: bool, multiline: bool) -> Quote {
+    Quote {
+        open,
+        close,
+        kind: QuoteKind::String,
+        escapes,
+        multiline,
+    }
+}
+
+const DOUBLE: Quote = quote("\"", "\"", true, false);
+const DOUBLE_MULTILINE: Quote = quote("\"", "\"", true, true);
+const SINGLE: Quote = quote("'", "'", true, false);
+const SINGLE_RAW: Quote = quote("'", "'", false, true);
+const BACKTICK: Quote = quote("`", "`", true, true);
+const BACKTICK_RAW: Quote = quote("`", "`", false, true);
+const TRIPLE_DOUBLE: Quote = quote("\"\"\"", "\"\"\"", true, true);
+const TRIPLE_SINGLE: Quote = quote("'''", "'''", true, true);
+const CHAR: Quote = Quote {
+    open: "'",
+    close: "'",
+    kind: QuoteKind::Char,
+    escapes: true,
+    multiline: false,
+};
+// SQL doubles the quote instead of escaping it, which scans as two adjacent literals
+const SQL_SINGLE: Quote = quote("'", "'", false, true);
+
+// How comments and string literals are written in a language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommentSyntax {
+    pub line: &'static [&'static str],
+    pub block: &'static [(&'static str, &'static str)],
+    // Longer delimiters first, e.g. triple quotes before single ones
+    pub quotes: &'static [Quote],
+    // Block comments nest, as in Rust and Swift
+    pub nested_blocks: bool,
+    // Line comments only start a word, as in shells where `$#` and `${#x}` aren't comments
+    pub line_at_word_start: bool,
+    // Rust raw strings: r"...", r#"..."#
+    pub raw_strings: bool,
+    // JavaScript regex literals such as /https?:\/\//, where an operand can start
+    pub regex_literals: bool,
+}
+
+const fn syntax(
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+    quotes: &'static [Quote],
+) -> CommentSyntax {
+    CommentSyntax {
+        line,
+        block,
+        quotes,
+        nested_blocks: false,
+        line_at_word_start: false,
+        raw_strings: false,
+        regex_literals: false,
+    }
+}
+
+const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+
+const C_LIKE: CommentSyntax = syntax(&["//"], C_BLOCK, &[DOUBLE, CHAR]);
+const RUST: CommentSyntax = CommentSyntax {
+    nested_blocks: true,
+    raw_strings: true,
+    ..syntax(&["//"], C_BLOCK, &[DOUBLE_MULTILINE, CHAR])
+};
+const SWIFT: CommentSyntax = CommentSyntax {
+    nested_blocks: true,
+    ..syntax(&["//"], C_BLOCK, &[TRIPLE_DOUBLE, DOUBLE])
+};
+const GO: CommentSyntax = syntax(&["//"], C_BLOCK, &[DOUBLE, CHAR, BACKTICK_RAW]);
+const JAVASCRIPT: CommentSyntax = CommentSyntax {
+    regex_literals: true,
+    ..syntax(&["//"], C_BLOCK, &[DOUBLE, SINGLE, BACKTICK])
+};
+const JVM: CommentSyntax = syntax(&["//"], C_BLOCK, &[TRIPLE_DOUBLE, DOUBLE, CHAR]);
+const DART: CommentSyntax = syntax(
+    &["//"],
+    C_BLOCK,
+    &[TRIPLE_DOUBLE, TRIPLE_SINGLE, DOUBLE, SINGLE],
+);
+const PHP: CommentSyntax = syntax(&["//", "#"], C_BLOCK, &[DOUBLE_MULTILINE, SINGLE_RAW]);
+const CSS: CommentSyntax = syntax(&[], C_BLOCK, &[DOUBLE, SINGLE]);
+const CSS_PREPROCESSOR: CommentSyntax = syntax(&["//"], C_BLOCK, &[DOUBLE, SINGLE]);
+const HASH_C_BLOCK: CommentSyntax = syntax(&["#", "//"], C_BLOCK, &[DOUBLE, SINGLE]);
+const PYTHON: CommentSyntax = syntax(&["#"], &[], &[TRIPLE_DOUBLE, TRIPLE_SINGLE, DOUBLE, SINGLE]);
+const HASH: CommentSyntax = syntax(&["#"], &[], &[DOUBLE, SINGLE]);
+// `$#array` is the last index of an array, not a comment
+const PERL: CommentSyntax = CommentSyntax {
+    line_at_word_start: true,
+    ..HASH
+};
+const SHELL: CommentSyntax = CommentSyntax {
+    line_at_word_start: true,
+    ..syntax(&["#"], &[], &[DOUBLE_MULTILINE, SINGLE_RAW])
+};
+const YAML: CommentSyntax = CommentSyntax {
+    line_at_word_start: true,
+    ..syntax(&["#"], &[], &[DOUBLE, SINGLE_RAW])
+};
+const TOML: CommentSyntax = syntax(
+    &["#"],
+    &[],
+    &[TRIPLE_DOUBLE, TRIPLE_SINGLE, DOUBLE, SINGLE_RAW],
+);
+const HASH_ONLY: CommentSyntax = CommentSyntax {
+    line_at_word_start: true,
+    ..syntax(&["#"], &[], &[])
+};
+const NIX: CommentSyntax = syntax(&["#"], C_BLOCK, &[DOUBLE_MULTILINE]);
+const POWERSHELL: CommentSyntax = syntax(&["#"], &[("<#", "#>")], &[DOUBLE, SINGLE_RAW]);
+const SQL: CommentSyntax = syntax(&["--"], C_BLOCK, &[SQL_SINGLE, DOUBLE]);
+const LUA: CommentSyntax = syntax(&["--"], &[("--[[", "]]")], &[DOUBLE, SINGLE]);
+const HASKELL: CommentSyntax = CommentSyntax {
+    nested_blocks: true,
+    ..syntax(&["--"], &[("{-", "-}")], &[DOUBLE])
+};
+const DASH_DASH: CommentSyntax = syntax(&["--"], &[], &[DOUBLE]);
+const ML: CommentSyntax = CommentSyntax {
+    nested_blocks: true,
+    ..syntax(&[], &[("(*", "*)")], &[DOUBLE])
+};
+const FSHARP: CommentSyntax = syntax(&["//"], &[("(*", "*)")], &[TRIPLE_DOUBLE, DOUBLE]);
+const LISP: CommentSyntax = syntax(&[";"], &[], &[DOUBLE]);
+const PERCENT: CommentSyntax = syntax(&["%"], &[], &[DOUBLE, SINGLE_RAW]);
+const INI: CommentSyntax = CommentSyntax {
+    line_at_word_start: true,
+    ..syntax(&[";", "#"], &[], &[])
+};
+const MARKUP: CommentSyntax = syntax(&[], &[("<!--", "-->")], &[]);
+
+// Keyed by the names languages::detect_language returns
+pub const COMMENT_SYNTAX: &[(&str, CommentSyntax)] = &[
+    ("c", C_LIKE),
+    ("cpp", C_LIKE),
+    ("objectivec", C_LIKE),
+    ("csharp", C_LIKE),
+    ("cuda", C_LIKE),
+    ("d", C_LIKE),
+    ("glsl", C_LIKE),
+    ("hlsl", C_LIKE),
+    ("wgsl", C_LIKE),
+    ("java", C_LIKE),
+    ("solidity", C_LIKE),
+    ("protobuf", C_LIKE),
+    ("thrift", C_LIKE),
+    ("zig", C_LIKE),
+    ("odin", C_LIKE),
+    ("v", C_LIKE),
+    ("jsonc", C_LIKE),
+    ("json5", JAVASCRIPT),
+    ("rust", RUST),
+    ("swift", SWIFT),
+    ("go", GO),
+    ("javascript", JAVASCRIPT),
+    ("typescript", JAVASCRIPT),
+    ("jsx", JAVASCRIPT),
+    ("tsx", JAVASCRIPT),
+    ("kotlin", JVM),
+    ("scala", JVM),
+    ("groovy", JVM),
+    ("dart", DART),
+    ("php", PHP),
+    ("css", CSS),
+    ("scss", CSS_PREPROCESSOR),
+    ("less", CSS_PREPROCESSOR),
+    ("stylus", CSS_PREPROCESSOR),
+    ("hcl", HASH_C_BLOCK),
+    ("jsonnet", HASH_C_BLOCK),
+    ("python", PYTHON),
+    ("cython", PYTHON),
+    ("starlark", PYTHON),
+    ("ruby", HASH),
+    ("crystal", HASH),
+    ("elixir", PYTHON),
+    ("perl", PERL),
+    ("r", HASH),
+    ("julia", PYTHON),
+    ("nim", PYTHON),
+    ("cmake", HASH),
+    ("makefile", SHELL),
+    ("dockerfile", SHELL),
+    ("just", SHELL),
+    ("bash", SHELL),
+    ("zsh", SHELL),
+    ("fish", SHELL),
+    ("awk", HASH),
+    ("tcl", SHELL),
+    ("dotenv", SHELL),
+    ("gitignore", HASH_ONLY),
+    ("gitattributes", HASH_ONLY),
+    ("nginx", SHELL),
+    ("graphql", HASH),
+    ("rego", HASH),
+    ("yaml", YAML),
+    ("toml", TOML),
+    ("nix", NIX),
+    ("powershell", POWERSHELL),
+    ("sql", SQL),
+    ("lua", LUA),
+    ("haskell", HASKELL),
+    ("purescript", HASKELL),
+    ("elm", HASKELL),
+    ("ada", DASH_DASH),
+    ("vhdl", DASH_DASH),
+    ("ocaml", ML),
+    ("fsharp", FSHARP),
+    ("lisp", LISP),
+    ("clojure", LISP),
+    ("scheme", LISP),
+    ("racket", LISP),
+    ("elisp", LISP),
+    ("erlang", PERCENT),
+    ("matlab", PERCENT),
+    ("ini", INI),
+    ("properties", INI),
+    ("html", MARKUP),
+    ("xml", MARKUP),
+];
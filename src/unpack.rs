@@ -0,0 +1,208 @@
+use anyhow::{anyhow, Context, Result};
+use similar::TextDiff;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::cli::Cli;
+use crate::parsers::{detect_format, parse_output, ParsedFile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnpackAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnpackEntry {
+    pub path: PathBuf,
+    pub target: PathBuf,
+    pub action: UnpackAction,
+    pub content: String,
+    // Current content on disk, for updates
+    pub existing: Option<String>,
+}
+
+// Only plain relative paths may be written, so a bundle can't escape the output directory
+pub fn safe_relative_path(path: &Path) -> Result<PathBuf> {
+    let mut safe = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => safe.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(anyhow!(
+                    "Refusing to unpack {}: path must stay inside the output directory",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    if safe.as_os_str().is_empty() {
+        return Err(anyhow!("Refusing to unpack a file with an empty path"));
+    }
+
+    Ok(safe)
+}
+
+// Symlinks could point anywhere, so a target may not be one, and its nearest existing parent
+// directory must resolve inside the output directory
+fn ensure_inside(root: &Path, target: &Path) -> Result<()> {
+    let escapes = || {
+        anyhow!(
+            "Refusing to unpack {}: it resolves outside {}",
+            target.display(),
+            root.display()
+        )
+    };
+
+    if let Ok(metadata) = std::fs::symlink_metadata(target) {
+        if metadata.file_type().is_symlink() {
+            return Err(escapes());
+        }
+        if metadata.is_dir() {
+            return Err(anyhow!(
+                "Refusing to unpack {}: it is a directory",
+                target.display()
+            ));
+        }
+    }
+
+    // Nothing inside an output directory that doesn't exist yet can point elsewhere
+    if !root.exists() {
+        return Ok(());
+    }
+    let canonical_root = root.canonicalize()?;
+    let mut existing = target.parent();
+    while let Some(dir) = existing {
+        if dir.exists() {
+            if !dir.canonicalize()?.starts_with(&canonical_root) {
+                return Err(escapes());
+            }
+            return Ok(());
+        }
+        existing = dir.parent();
+    }
+    Ok(())
+}
+
+pub fn plan_unpack(files: Vec<ParsedFile>, root: &Path) -> Result<Vec<UnpackEntry>> {
+    // Validate every path before anything is written
+    let mut entries = Vec::new();
+    for file in files {
+        let path = safe_relative_path(&file.path)?;
+        let target = root.join(&path);
+        ensure_inside(root, &target)?;
+
+        let existing = match target.is_file() {
+            true => Some(
+                std::fs::read_to_string(&target)
+                    .with_context(|| format!("Failed to read {}", target.display()))?,
+            ),
+            false => None,
+        };
+        let action = match &existing {
+            None => UnpackAction::Create,
+            Some(current) if *current == file.content => UnpackAction::Unchanged,
+            Some(_) => UnpackAction::Update,
+        };
+
+        entries.push(UnpackEntry {
+            path,
+            target,
+            action,
+            content: file.content,
+            existing,
+        });
+    }
+    Ok(entries)
+}
+
+// Expects entries from plan_unpack, which has already checked that every target is safe
+pub fn apply_unpack(entries: &[UnpackEntry], root: &Path) -> Result<()> {
+    std::fs::create_dir_all(root)?;
+    for entry in entries {
+        if entry.action == UnpackAction::Unchanged {
+            continue;
+        }
+        if let Some(parent) = entry.target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&entry.target, &entry.content)
+            .with_context(|| format!("Failed to write {}", entry.target.display()))?;
+    }
+    Ok(())
+}
+
+fn read_input(input: &Path) -> Result<String> {
+    if input == Path::new("-") {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        return Ok(buffer);
+    }
+    std::fs::read_to_string(input)
+        .with_context(|| format!("Failed to read bundle {}", input.display()))
+}
+
+pub fn process_unpack(input: &Path, cli: &Cli) -> Result<()> {
+    let bundle = read_input(input)?;
+    let format = cli
+        .chosen_format()
+        .unwrap_or_else(|| detect_format(&bundle));
+    let files = parse_output(&bundle, &format)?;
+
+    if files.is_empty() {
+        return Err(anyhow!("No files found in {}", input.display()));
+    }
+
+    let root = &cli.unpack_dir;
+    let entries = plan_unpack(files, root)?;
+
+    for entry in &entries {
+        let label = match entry.action {
+            UnpackAction::Create => "create",
+            UnpackAction::Update => "update",
+            UnpackAction::Unchanged => "unchanged",
+        };
+        println!("{:>9} {}", label, entry.target.display());
+
+        if cli.diff && entry.action != UnpackAction::Unchanged {
+            let old = entry.existing.as_deref().unwrap_or("");
+            let old_name = format!("a/{}", entry.path.display());
+            let new_name = format!("b/{}", entry.path.display());
+            print!(
+                "{}",
+                TextDiff::from_lines(old, &entry.content)
+                    .unified_diff()
+                    .header(&old_name, &new_name)
+            );
+        }
+    }
+
+    let changed = entries
+        .iter()
+        .filter(|entry| entry.action != UnpackAction::Unchanged)
+        .count();
+
+    if cli.dry_run {
+        println!(
+            "Dry run: {} of {} files would be written to {} ({:?} format)",
+            changed,
+            entries.len(),
+            root.display(),
+            format
+        );
+        return Ok(());
+    }
+
+    apply_unpack(&entries, root)?;
+    println!(
+        "Unpacked {} of {} files into {}",
+        changed,
+        entries.len(),
+        root.display()
+    );
+
+    Ok(())
+}
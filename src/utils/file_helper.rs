@@ -2,7 +2,7 @@ use anyhow::Result;
 use atty;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use std::io::{self, Read};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub fn build_globset(patterns: &[String]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
@@ -68,3 +68,19 @@ pub fn read_paths_from_stdin(use_null_separator: bool) -> Result<Vec<PathBuf>> {
 
     Ok(paths)
 }
+
+// `path` made absolute against `cwd`, with `.` and `..` resolved without touching the file
+// system, so paths from --rev and files that don't exist can be compared too
+pub fn normalize_path(path: &Path, cwd: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
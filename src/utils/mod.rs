@@ -1,5 +1,11 @@
 mod api_key;
+mod date;
 mod file_helper;
+mod path_renderer;
 
 pub use api_key::get_api_key;
-pub use file_helper::{build_globset, read_paths_from_stdin, should_ignore};
+pub use date::format_date;
+pub use file_helper::{build_globset, normalize_path, read_paths_from_stdin, should_ignore};
+pub use path_renderer::{
+    anonymize_path, find_git_root, load_path_map, restore_paths, PathRenderer,
+};
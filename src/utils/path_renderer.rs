@@ -0,0 +1,218 @@
+use anyhow::{anyhow, Context, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::normalize_path;
+use crate::cli::Cli;
+use crate::models::PathMode;
+
+// Number of hex characters kept from each component hash
+const HASH_LENGTH: usize = 10;
+
+// Found lexically, so paths that only exist at --rev still have a root
+pub fn find_git_root(path: &Path, cwd: &Path) -> Option<PathBuf> {
+    normalize_path(path, cwd)
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+fn random_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).expect("Failed to generate a random key");
+    key
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hash_component(name: &str, key: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(name.as_bytes());
+    to_hex(&mac.finalize().into_bytes())[..HASH_LENGTH].to_string()
+}
+
+// Hashes every component with `key` but keeps the extension, so languages can still be detected
+pub fn anonymize_path(path: &Path, key: &[u8]) -> PathBuf {
+    let mut anonymized = PathBuf::new();
+    let mut components = path.components().peekable();
+
+    while let Some(component) = components.next() {
+        match component {
+            Component::Normal(name) => {
+                let name = Path::new(name);
+                let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+                let hashed = match name.extension() {
+                    Some(ext) if components.peek().is_none() => {
+                        format!("{}.{}", hash_component(&stem, key), ext.to_string_lossy())
+                    }
+                    _ => hash_component(&name.to_string_lossy(), key),
+                };
+                anonymized.push(hashed);
+            }
+            Component::ParentDir => anonymized.push(".."),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+
+    anonymized
+}
+
+pub struct PathRenderer {
+    mode: PathMode,
+    root: Option<PathBuf>,
+    prefix: Option<PathBuf>,
+    cwd: PathBuf,
+    // Random key for anonymized names, so common names like `src` or `home` can't be looked up
+    // in a table of hashes. It's stored in the --path-map file along with the mapping
+    key: [u8; 32],
+    // Rendered path -> original path, for every path that was rewritten
+    mapping: BTreeMap<String, String>,
+}
+
+impl PathRenderer {
+    pub fn new(mode: PathMode, root: Option<&Path>, prefix: Option<&str>) -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let root = match (&mode, root) {
+            (PathMode::Relative, Some(root)) if !root.is_dir() => {
+                return Err(anyhow!("Invalid path root {}", root.display()));
+            }
+            (PathMode::Relative, root) => Some(normalize_path(root.unwrap_or(&cwd), &cwd)),
+            _ => None,
+        };
+
+        Ok(Self {
+            mode,
+            root,
+            prefix: prefix.map(PathBuf::from),
+            cwd,
+            key: random_key(),
+            mapping: BTreeMap::new(),
+        })
+    }
+
+    pub fn from_cli(cli: &Cli) -> Result<Self> {
+        Self::new(
+            cli.path_mode.clone(),
+            cli.path_root.as_deref(),
+            cli.path_prefix.as_deref(),
+        )
+    }
+
+    // The key anonymized names are derived from
+    pub fn key(&self) -> &[u8; 32] {
+        &self.key
+    }
+
+    fn strip_root(&self, path: &Path, root: &Path) -> Option<PathBuf> {
+        let normalized = normalize_path(path, &self.cwd);
+        normalized.strip_prefix(root).ok().map(Path::to_path_buf)
+    }
+
+    pub fn render(&mut self, path: &Path) -> PathBuf {
+        let rendered = match self.mode {
+            PathMode::Walked => path.to_path_buf(),
+            PathMode::Relative => self
+                .root
+                .as_deref()
+                .and_then(|root| self.strip_root(path, root))
+                .unwrap_or_else(|| path.to_path_buf()),
+            PathMode::GitRoot => find_git_root(path, &self.cwd)
+                .and_then(|root| self.strip_root(path, &root))
+                .unwrap_or_else(|| path.to_path_buf()),
+            PathMode::Anonymized => anonymize_path(path, &self.key),
+        };
+
+        let rendered = match &self.prefix {
+            Some(prefix) => prefix.join(rendered),
+            None => rendered,
+        };
+
+        if rendered != path {
+            self.mapping.insert(
+                rendered.to_string_lossy().to_string(),
+                path.to_string_lossy().to_string(),
+            );
+        }
+
+        rendered
+    }
+
+    pub fn mapping(&self) -> &BTreeMap<String, String> {
+        &self.mapping
+    }
+
+    pub fn write_mapping(&self, path: &Path) -> Result<()> {
+        let map = PathMap {
+            key: matches!(self.mode, PathMode::Anonymized).then(|| to_hex(&self.key)),
+            paths: self.mapping.clone(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&map)?)
+            .with_context(|| format!("Failed to write path map {}", path.display()))
+    }
+}
+
+// What --path-map writes: rendered path -> original path, plus the key anonymized names were
+// derived from
+#[derive(Debug, Serialize, Deserialize)]
+struct PathMap {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    paths: BTreeMap<String, String>,
+}
+
+pub fn load_path_map(path: &Path) -> Result<BTreeMap<String, String>> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read path map {}", path.display()))?;
+    serde_json::from_str::<PathMap>(&json)
+        .map(|map| map.paths)
+        .map_err(|e| anyhow!("Invalid path map {}: {}", path.display(), e))
+}
+
+// Characters that can continue a path, so a rendered path next to one of them is only part of
+// a longer token
+fn is_path_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.' | '/' | '\\')
+}
+
+// A period followed by something other than a path character ends a sentence, not the path
+fn ends_token(after: &str) -> bool {
+    let mut chars = after.chars();
+    match chars.next() {
+        None => true,
+        Some('.') => !chars.next().is_some_and(is_path_char),
+        Some(ch) => !is_path_char(ch),
+    }
+}
+
+// Replaces rendered paths in `text` with the originals. Only whole paths are replaced, longest
+// first, so a name that is a prefix or suffix of a longer path is left alone
+pub fn restore_paths(text: &str, mapping: &BTreeMap<String, String>) -> String {
+    let mut entries: Vec<_> = mapping.iter().collect();
+    entries.sort_by_key(|(rendered, _)| std::cmp::Reverse(rendered.len()));
+
+    let mut restored = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        let at_token_start = !text[..index].chars().next_back().is_some_and(is_path_char);
+        let found = entries.iter().find(|(rendered, _)| {
+            at_token_start
+                && rest.starts_with(rendered.as_str())
+                && ends_token(&rest[rendered.len()..])
+        });
+        if let Some((rendered, original)) = found {
+            restored.push_str(original);
+            index += rendered.len();
+            continue;
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        restored.push(ch);
+        index += ch.len_utf8();
+    }
+    restored
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::path::Path;
+    use summarize::archive::{archive_name, ManifestEntry};
+    use summarize::formatters::Writer;
+    use summarize::languages::LanguageOverrides;
+    use summarize::models::{ArchiveFormat, Compression, TokenizerModel};
+
+    #[test]
+    fn test_compressed_output() {
+        let dir = tempfile::tempdir().unwrap();
+
+        for name in ["bundle.txt.gz", "bundle.txt.zst", "bundle.txt"] {
+            let path = dir.path().join(name);
+            let mut writer = Writer::create(&path).unwrap();
+            writer.write("hello").unwrap();
+            writer.write_raw("world").unwrap();
+            writer.finish().unwrap();
+
+            let file = std::fs::File::open(&path).unwrap();
+            let mut content = String::new();
+            match Compression::from_path(&path) {
+                Compression::Gzip => flate2::read::GzDecoder::new(file)
+                    .read_to_string(&mut content)
+                    .unwrap(),
+                Compression::Zstd => zstd::Decoder::new(file)
+                    .unwrap()
+                    .read_to_string(&mut content)
+                    .unwrap(),
+                Compression::None => std::io::BufReader::new(file)
+                    .read_to_string(&mut content)
+                    .unwrap(),
+            };
+            assert_eq!(content, "hello\nworld", "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_archive_names_and_manifest() {
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("out.tar.gz")).unwrap(),
+            ArchiveFormat::Tar
+        );
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("OUT.ZIP")).unwrap(),
+            ArchiveFormat::Zip
+        );
+        assert!(ArchiveFormat::from_path(Path::new("out.txt.gz")).is_err());
+
+        assert_eq!(
+            archive_name(Path::new("/home/me/src/main.rs")).as_deref(),
+            Some("home/me/src/main.rs")
+        );
+        assert_eq!(
+            archive_name(Path::new("../lib/./mod.rs")).as_deref(),
+            Some("lib/mod.rs")
+        );
+        assert_eq!(archive_name(Path::new("/")), None);
+
+        let entry = ManifestEntry::new(
+            "src/main.rs",
+            b"fn main() {}\n",
+            &TokenizerModel::Gpt4,
+            &LanguageOverrides::default(),
+        );
+        assert_eq!(entry.language.as_deref(), Some("rust"));
+        assert_eq!(entry.bytes, 13);
+        assert_eq!(entry.lines, 1);
+        assert!(entry.tokens > 0);
+        assert_eq!(entry.sha256.len(), 64);
+
+        let binary = ManifestEntry::new(
+            "logo.png",
+            &[0x89, 0x50, 0xff],
+            &TokenizerModel::Gpt4,
+            &LanguageOverrides::default(),
+        );
+        assert_eq!(binary.tokens, 0);
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use summarize::extractors::{
+        extract, lockfile_packages, render_notebook, sample_table, truncate_json, truncate_yaml,
+        ExtractOptions,
+    };
+    use summarize::models::Extractor;
+
+    #[test]
+    fn test_render_notebook() {
+        let notebook = r##"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n", "Text"]},
+                {"cell_type": "code", "source": "df.plot()", "outputs": [
+                    {"output_type": "stream", "text": ["a\n", "b\n", "c\n"]},
+                    {"output_type": "display_data", "data": {
+                        "image/png": "iVBORw0KGgo=", "text/plain": ["<Figure>"]}},
+                    {"output_type": "execute_result", "data": {"text/html": ["<table>"]}},
+                    {"output_type": "error", "ename": "ValueError", "evalue": "bad"}
+                ]}
+            ],
+            "nbformat": 4
+        }"##;
+        assert_eq!(
+            render_notebook(notebook, 20).unwrap(),
+            "# %% [markdown]\n# Title\nText\n\n# %%\ndf.plot()\n# Output:\n# a\n# b\n# c\n# <Figure>\n# [image/png output omitted]\n# [text/html output omitted]\n# ValueError: bad\n"
+        );
+        assert_eq!(
+            render_notebook(notebook, 2).unwrap(),
+            "# %% [markdown]\n# Title\nText\n\n# %%\ndf.plot()\n# Output:\n# a\n# b\n# ... 5 more lines\n"
+        );
+        assert!(render_notebook("not json", 20).is_none());
+    }
+
+    #[test]
+    fn test_tables_and_data() {
+        let csv = "id,note\n1,\"two\nlines, \"\"quoted\"\"\"\n2,12\" pipe\n3,c\n";
+        assert_eq!(
+            sample_table(csv, 2, true),
+            "id,note\n1,\"two\nlines, \"\"quoted\"\"\"\n2,12\" pipe\n... 1 more rows\n"
+        );
+        assert_eq!(sample_table(csv, 3, true), csv);
+
+        // TSV has no quoting
+        let tsv = "id\tsize\n1\t12\" pipe\n2\t3\"\n3\tx\n";
+        assert_eq!(
+            sample_table(tsv, 1, false),
+            "id\tsize\n1\t12\" pipe\n... 2 more rows\n"
+        );
+
+        let json = r#"{"z": 1, "a": {"b": {"c": [1, 2]}, "d": []}, "list": [{"x": "}"}, 2]}"#;
+        assert_eq!(
+            truncate_json(json, 2).unwrap(),
+            "{\n  \"z\": 1,\n  \"a\": {\n    \"b\": {... 1 key},\n    \"d\": []\n  },\n  \"list\": [\n    {... 1 key},\n    2\n  ]\n}\n"
+        );
+        assert!(truncate_json("{\"a\": 1, // comment\n}", 2).is_none());
+
+        let yaml = "a:\n  b:\n    c: 1\n    d:\n      - 1\n  e: 2\nf: 3\n";
+        assert_eq!(
+            truncate_yaml(yaml, 2),
+            "a:\n  b:\n    # ... 3 more lines\n  e: 2\nf: 3\n"
+        );
+    }
+
+    #[test]
+    fn test_lockfiles() {
+        let pair = |name: &str, version: &str| (name.to_string(), version.to_string());
+
+        let cargo = "version = 3\n\n[[package]]\nname = \"b\"\nversion = \"1.0.0\"\ndependencies = [\n \"a\",\n]\n\n[[package]]\nname = \"a\"\nversion = \"0.2.0\"\n";
+        assert_eq!(
+            lockfile_packages("Cargo.lock", cargo).unwrap(),
+            vec![pair("a", "0.2.0"), pair("b", "1.0.0")]
+        );
+
+        let npm = r#"{"packages": {"": {"name": "app"},
+            "node_modules/@s/x": {"version": "1.0.0"},
+            "node_modules/@s/x/node_modules/y": {"version": "2.0.0"}}}"#;
+        assert_eq!(
+            lockfile_packages("package-lock.json", npm).unwrap(),
+            vec![pair("@s/x", "1.0.0"), pair("y", "2.0.0")]
+        );
+
+        let yarn = "# yarn lockfile v1\n\n\"@s/x@^1.0.0\", \"@s/x@^1.1.0\":\n  version \"1.2.0\"\n  resolved \"...\"\n\nleft-pad@npm:^1.3.0:\n  version: 1.3.0\n";
+        assert_eq!(
+            lockfile_packages("yarn.lock", yarn).unwrap(),
+            vec![pair("@s/x", "1.2.0"), pair("left-pad", "1.3.0")]
+        );
+
+        let pnpm = "lockfileVersion: '6.0'\n\npackages:\n\n  /@s/x@1.0.0(react@18.0.0):\n    resolution: {}\n\n  /y/2.0.0:\n    dev: true\n";
+        assert_eq!(
+            lockfile_packages("pnpm-lock.yaml", pnpm).unwrap(),
+            vec![pair("@s/x", "1.0.0"), pair("y", "2.0.0")]
+        );
+
+        let go_sum = "golang.org/x/text v0.3.0 h1:abc=\ngolang.org/x/text v0.3.0/go.mod h1:def=\n";
+        assert_eq!(
+            lockfile_packages("go.sum", go_sum).unwrap(),
+            vec![pair("golang.org/x/text", "v0.3.0")]
+        );
+
+        // Lockfiles are summarized even when --extract data would also apply
+        let options = ExtractOptions {
+            table_rows: 10,
+            data_depth: 1,
+            cell_output_lines: 20,
+        };
+        let extractors = [Extractor::Data, Extractor::Lockfiles];
+        assert_eq!(
+            extract(
+                npm,
+                Path::new("web/package-lock.json"),
+                &extractors,
+                &options
+            )
+            .unwrap(),
+            "2 packages\n@s/x 1.0.0\ny 2.0.0\n"
+        );
+        assert!(extract(cargo, Path::new("Cargo.toml"), &extractors, &options).is_none());
+    }
+}
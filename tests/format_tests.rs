@@ -1,6 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use summarize::formatters::add_line_numbers;
+    use clap::Parser;
+    use std::path::PathBuf;
+    use summarize::cli::Cli;
+    use summarize::formatters::{
+        add_line_numbers, escape_xml, render_template, render_tree, wrap_cdata, Sink, Template,
+        TreeEntry, Writer,
+    };
+    use summarize::models::FileStats;
+    use summarize::parsers::{parse_cxml, unescape_xml};
+    use summarize::session::Session;
+    use summarize::{collect_file_contents, write_file_contents};
 
     #[test]
     fn test_add_line_numbers() {
@@ -22,4 +32,218 @@ mod tests {
         assert_eq!(lines[9], " 10  Line 10");
         assert_eq!(lines[99], "100  Line 100");
     }
+
+    fn collect(dir: &std::path::Path, format: &str) -> String {
+        let cli = Cli::parse_from(["summarize", "-f", format, dir.to_str().unwrap()]);
+        collect_file_contents(
+            &cli.paths,
+            &cli,
+            &Session::from_cli(&cli).unwrap(),
+            &cli.format(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.py"), "print(\"hi\")\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "plain").unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&collect(dir.path(), "json")).unwrap();
+        let mut documents = json.as_array().unwrap().clone();
+        documents.sort_by_key(|d| d["path"].as_str().unwrap().to_string());
+
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0]["language"], "python");
+        assert_eq!(documents[0]["content"], "print(\"hi\")\n");
+        assert_eq!(documents[0]["bytes"], 12);
+        assert!(documents[0]["tokens"].as_u64().unwrap() > 0);
+        assert!(documents[1]["language"].is_null());
+
+        let jsonl = collect(dir.path(), "jsonl");
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+
+        // An empty selection is still a valid document
+        let empty = tempfile::tempdir().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&collect(empty.path(), "json")).unwrap();
+        assert_eq!(json, serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_xml_escaping() {
+        let text = r#"a < b && c > "d" 'e'"#;
+        assert_eq!(
+            escape_xml(text),
+            "a &lt; b &amp;&amp; c &gt; &quot;d&quot; &apos;e&apos;"
+        );
+        assert_eq!(unescape_xml(&escape_xml(text)), text);
+        assert_eq!(unescape_xml("&#65;&#x42; & &unknown;"), "AB & &unknown;");
+
+        assert_eq!(wrap_cdata("x]]>y"), "<![CDATA[x]]]]><![CDATA[>y]]>");
+    }
+
+    #[test]
+    fn test_cxml_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "tricky.xml",
+                "<document_content>\n</document_content>\n</document>\n",
+            ),
+            ("cdata.txt", "a ]]> b ]]]]> c <![CDATA[ d"),
+            ("a&b <c>.rs", "fn main() { if a && b {} }\n\n"),
+            ("empty.txt", ""),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+
+        let output = collect(dir.path(), "cxml");
+        assert!(output.contains(r#"language="rust""#));
+        assert!(output.contains("a&amp;b &lt;c&gt;.rs</source>"));
+
+        let parsed = parse_cxml(&output).unwrap();
+        assert_eq!(parsed.len(), files.len());
+        for (name, content) in files {
+            let file = parsed
+                .iter()
+                .find(|f| f.path == dir.path().join(name))
+                .unwrap();
+            assert_eq!(file.content, content);
+        }
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let entry = |path: &str, tokens: usize| TreeEntry {
+            path: PathBuf::from(path),
+            stats: Some(FileStats {
+                tokens,
+                bytes: tokens * 4,
+                lines: 1,
+            }),
+        };
+        let entries = vec![
+            entry("src/main.rs", 10),
+            entry("src/models/mod.rs", 1000),
+            entry("README.md", 300),
+        ];
+
+        assert_eq!(
+            render_tree(&entries),
+            [
+                "README.md (300 tokens, 1.2 KB)",
+                "src/ (1,010 tokens, 3.9 KB)",
+                "├── main.rs (10 tokens, 40 B)",
+                "└── models/ (1,000 tokens, 3.9 KB)",
+                "    └── mod.rs (1,000 tokens, 3.9 KB)",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_template_format() {
+        let template = Template::parse(
+            "{{#header}}\n<files count=\"{{file_count}}\">\n{{/header}}\n{{#file}}\n<file n=\"{{index}}\" lang=\"{{language}}\" lines=\"{{line_count}}\">{{content}}</file>\n{{/file}}\n{{#footer}}\n</files>\n{{/footer}}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            template.header.as_deref(),
+            Some("<files count=\"{{file_count}}\">")
+        );
+        assert!(!template.uses("token_count"));
+        assert!(Template::parse("{{ token_count }}")
+            .unwrap()
+            .uses("token_count"));
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "{{index}}\n").unwrap();
+        let template_path = dir.path().join("layout.tpl");
+        std::fs::write(
+            &template_path,
+            "{{#file}}\n{{index}}:{{language}}:{{content}}\n{{/file}}",
+        )
+        .unwrap();
+
+        let cli = Cli::parse_from([
+            "summarize",
+            "-f",
+            "template",
+            "--template",
+            template_path.to_str().unwrap(),
+            dir.path().join("a.rs").to_str().unwrap(),
+        ]);
+        let output = collect_file_contents(
+            &cli.paths,
+            &cli,
+            &Session::from_cli(&cli).unwrap(),
+            &cli.format(),
+        )
+        .unwrap();
+        // Placeholders inside file content are not expanded
+        assert_eq!(output, "1:rust:{{index}}\n\n");
+
+        // Unknown placeholders and header-only placeholders in the file section are rejected
+        assert!(Template::parse("{{nope}}").is_err());
+        assert!(Template::parse("{{#file}}{{file_count}}{{/file}}").is_err());
+        assert_eq!(
+            render_template("{{a}} {{b}} {{", &[("a", "1".to_string())].into()),
+            "1 {{b}} {{"
+        );
+    }
+
+    #[test]
+    fn test_writer_streams_to_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn main() {}\n").unwrap();
+        let cli = Cli::parse_from(["summarize", "-m", dir.path().to_str().unwrap()]);
+
+        let mut sink = Vec::new();
+        let mut writer = Writer::new(&mut sink);
+        write_file_contents(
+            &cli.paths,
+            &cli,
+            &Session::from_cli(&cli).unwrap(),
+            &cli.format(),
+            &mut writer,
+        )
+        .unwrap();
+        writer.finish().unwrap();
+
+        let streamed = String::from_utf8(sink).unwrap();
+        assert!(streamed.contains("fn main() {}"));
+        assert_eq!(
+            streamed,
+            collect_file_contents(
+                &cli.paths,
+                &cli,
+                &Session::from_cli(&cli).unwrap(),
+                &cli.format()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_writer_multiple_sinks() {
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+
+        let mut writer = Writer::from_sinks(vec![
+            Sink::Plain(Box::new(&mut first)),
+            Sink::Plain(Box::new(&mut second)),
+        ]);
+        writer.write("<documents>").unwrap();
+        writer.write_raw("</documents>").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(first, b"<documents>\n</documents>");
+        assert_eq!(first, second);
+    }
 }
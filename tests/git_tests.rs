@@ -0,0 +1,413 @@
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use git2::{Repository, Signature};
+    use std::path::{Path, PathBuf};
+    use summarize::changelog::{chunk_history, prepend_entry};
+    use summarize::cli::Cli;
+    use summarize::git::{
+        changed_files, commit_history, content_after, diff_range, file_histories, filter_changed,
+        FileHistories, RevisionRange, Snapshot, SnapshotOptions,
+    };
+    use summarize::models::TokenizerModel;
+    use summarize::models::{DiffStatus, GitSelection, TokenDiff};
+    use summarize::session::Session;
+    use summarize::{count_tokens, read_content, select_files};
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    fn changed(repo: &Repository, selection: GitSelection) -> Vec<PathBuf> {
+        let mut files = changed_files(repo, &selection).unwrap();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let write = |name: &str, content: &str| {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        };
+
+        write("base.rs", "fn base() {}\n");
+        write("gone.rs", "fn gone() {}\n");
+        commit_all(&repo, "initial");
+        let fork = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+        write("feature.rs", "fn feature() {}\n");
+        std::fs::remove_file(dir.path().join("gone.rs")).unwrap();
+        commit_all(&repo, "feature");
+
+        // Staged but not committed
+        write("staged.rs", "fn staged() {}\n");
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("staged.rs")).unwrap();
+        index.write().unwrap();
+
+        // Modified and untracked in the working tree
+        write("base.rs", "fn base() { changed() }\n");
+        write("new.rs", "fn new() {}\n");
+
+        assert_eq!(
+            changed(&repo, GitSelection::Staged),
+            vec![PathBuf::from("staged.rs")]
+        );
+        assert_eq!(
+            changed(&repo, GitSelection::Uncommitted),
+            vec![
+                PathBuf::from("base.rs"),
+                PathBuf::from("new.rs"),
+                PathBuf::from("staged.rs")
+            ]
+        );
+        assert_eq!(
+            changed(&repo, GitSelection::ChangedSince(fork.to_string())),
+            vec![
+                PathBuf::from("base.rs"),
+                PathBuf::from("feature.rs"),
+                PathBuf::from("new.rs"),
+                PathBuf::from("staged.rs")
+            ]
+        );
+        assert!(changed_files(&repo, &GitSelection::ChangedSince("missing".into())).is_err());
+    }
+
+    #[test]
+    fn test_diff_range() {
+        let range = RevisionRange::parse("main...feature").unwrap();
+        assert_eq!(range.from, "main");
+        assert_eq!(range.to.as_deref(), Some("feature"));
+        assert!(range.merge_base);
+        assert_eq!(RevisionRange::parse("HEAD~2").unwrap().to, None);
+        assert!(RevisionRange::parse("..HEAD").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn one() {}\nfn two() {}\n").unwrap();
+        std::fs::write(dir.path().join("old.rs"), "fn old() {}\n").unwrap();
+        commit_all(&repo, "initial");
+        let base = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .id()
+            .to_string();
+
+        std::fs::write(dir.path().join("lib.rs"), "fn one() {}\nfn three() {}\n").unwrap();
+        std::fs::remove_file(dir.path().join("old.rs")).unwrap();
+        commit_all(&repo, "change");
+
+        let range = RevisionRange::parse(&format!("{}..HEAD", base)).unwrap();
+        let patches = diff_range(&repo, &range, 0).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].path, PathBuf::from("lib.rs"));
+        assert!(patches[0].patch.contains("-fn two() {}\n+fn three() {}"));
+        assert!(!patches[0].patch.contains("\n fn one() {}"));
+        assert_eq!(patches[1].status, git2::Delta::Deleted);
+
+        assert_eq!(
+            content_after(&repo, &range, Path::new("lib.rs")).unwrap(),
+            Some("fn one() {}\nfn three() {}\n".to_string())
+        );
+        assert_eq!(
+            content_after(&repo, &range, Path::new("old.rs")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_commit_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        commit_all(&repo, "Initial commit");
+        std::fs::write(dir.path().join("a.rs"), "fn a() { b() }\nfn b() {}\n").unwrap();
+        commit_all(&repo, "Add b\n\nCalled from a.");
+        std::fs::write(dir.path().join("c.rs"), "fn c() {}\n").unwrap();
+        commit_all(&repo, "Add c");
+
+        let range = RevisionRange::parse("HEAD~2").unwrap();
+        let commits = commit_history(&repo, &range, true).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].summary(), "Add b");
+        assert_eq!(commits[0].files[0].insertions, 2);
+        assert_eq!(commits[0].files[0].deletions, 1);
+        assert!(commits[0].diff.as_ref().unwrap().contains("+fn b() {}"));
+
+        let rendered = commits[0].render(false);
+        assert!(rendered.contains("    Add b\n\n    Called from a.\n"));
+        assert!(rendered.contains(" a.rs | +2 -1\n"));
+        assert!(!rendered.contains("+fn b() {}"));
+
+        // Everything fits in one chunk, or each commit gets its own
+        let model = TokenizerModel::Gpt4;
+        assert_eq!(chunk_history(&commits, 100_000, &model).len(), 1);
+        assert_eq!(chunk_history(&commits, 10, &model).len(), 2);
+    }
+
+    #[test]
+    fn test_prepend_entry() {
+        let entry = "## v2 - 2024-02-01\n\n### Added\n\n- New thing\n";
+        assert_eq!(
+            prepend_entry("", entry),
+            format!("# Changelog\n\n{}", entry)
+        );
+        assert_eq!(
+            prepend_entry("# Changelog\n\n## v1 - 2024-01-01\n", entry),
+            format!("# Changelog\n\n{}\n## v1 - 2024-01-01\n", entry)
+        );
+        assert_eq!(
+            prepend_entry("## v1 - 2024-01-01\n", entry),
+            format!("{}\n## v1 - 2024-01-01\n", entry)
+        );
+    }
+
+    #[test]
+    fn test_revision_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+        std::fs::create_dir(root.join("logs")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("logs/.gitignore"), "!keep.log\n").unwrap();
+        std::fs::write(root.join("logs/keep.log"), "kept\n").unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("logs/keep.log")).unwrap();
+        index.write().unwrap();
+        commit_all(&repo, "initial");
+
+        // Tracked despite the .gitignore, like a force-added file
+        std::fs::write(root.join("debug.log"), "noise\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("debug.log")).unwrap();
+        index.write().unwrap();
+        commit_all(&repo, "add log");
+
+        // Later working tree changes are not seen
+        std::fs::write(root.join("main.rs"), "fn main() { changed() }\n").unwrap();
+        std::fs::write(root.join("extra.rs"), "fn extra() {}\n").unwrap();
+
+        let options = SnapshotOptions {
+            gitignore: true,
+            hidden: false,
+        };
+        let snapshot = Snapshot::open(&root, "HEAD", options).unwrap();
+        assert_eq!(
+            snapshot.files_under(&root),
+            vec![root.join("logs/keep.log"), root.join("main.rs")]
+        );
+        assert_eq!(
+            snapshot.files_under(&root.join("logs")),
+            vec![root.join("logs/keep.log")]
+        );
+        assert_eq!(
+            snapshot.read(&root.join("main.rs")).unwrap(),
+            b"fn main() {}\n"
+        );
+        assert!(snapshot.read(&root.join("extra.rs")).is_err());
+
+        let options = SnapshotOptions {
+            gitignore: false,
+            hidden: true,
+        };
+        let snapshot = Snapshot::open(&root, "HEAD~1", options).unwrap();
+        assert_eq!(snapshot.files_under(&root).len(), 4);
+
+        // Sessions at different revisions can be used side by side
+        let session = |args: &[&str]| {
+            let mut argv = vec!["summarize", root.to_str().unwrap()];
+            argv.extend(args);
+            let cli = Cli::parse_from(argv);
+            let session = Session::from_cli(&cli).unwrap();
+            let files = select_files(&cli.paths, &cli, &session).unwrap();
+            let main = read_content(&root.join("main.rs"), &cli, &session).unwrap();
+            (files.len(), main)
+        };
+        assert_eq!(
+            session(&["--rev", "HEAD"]),
+            (2, "fn main() {}\n".to_string())
+        );
+        assert_eq!(session(&[]), (3, "fn main() { changed() }\n".to_string()));
+    }
+
+    #[test]
+    fn test_index_snapshot() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let roots = [
+            first.path().canonicalize().unwrap(),
+            second.path().canonicalize().unwrap(),
+        ];
+        for root in &roots {
+            let repo = Repository::init(root).unwrap();
+            std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+            std::fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+            commit_all(&repo, "initial");
+
+            // Staged, then changed again in the working tree
+            std::fs::write(root.join("main.rs"), "fn main() { staged() }\n").unwrap();
+            std::fs::write(root.join("debug.log"), "forced\n").unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("main.rs")).unwrap();
+            index.add_path(Path::new("debug.log")).unwrap();
+            index.write().unwrap();
+            std::fs::write(root.join("main.rs"), "fn main() { unstaged() }\n").unwrap();
+            std::fs::write(root.join("untracked.rs"), "\n").unwrap();
+        }
+
+        let options = SnapshotOptions {
+            gitignore: true,
+            hidden: false,
+        };
+        let snapshot = Snapshot::from_index(roots.iter().map(PathBuf::as_path), options).unwrap();
+        for root in &roots {
+            assert_eq!(snapshot.files_under(root), vec![root.join("main.rs")]);
+            assert_eq!(
+                snapshot.read(&root.join("main.rs")).unwrap(),
+                b"fn main() { staged() }\n"
+            );
+        }
+
+        // --staged reads what was staged in each input's repository
+        let mut argv = vec!["summarize", "--staged"];
+        argv.extend(roots.iter().map(|root| root.to_str().unwrap()));
+        let cli = Cli::parse_from(argv);
+        let session = Session::from_cli(&cli).unwrap();
+        let files = select_files(&cli.paths, &cli, &session).unwrap();
+        assert_eq!(
+            files,
+            vec![roots[0].join("main.rs"), roots[1].join("main.rs")]
+        );
+        assert_eq!(
+            read_content(&roots[1].join("main.rs"), &cli, &session).unwrap(),
+            "fn main() { staged() }\n"
+        );
+    }
+
+    #[test]
+    fn test_token_counts_at_revisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+        commit_all(&repo, "initial");
+        std::fs::write(root.join("main.rs"), "fn main() { run(1, 2, 3) }\n").unwrap();
+        std::fs::write(root.join("lib.rs"), "pub fn run() {}\n").unwrap();
+        commit_all(&repo, "grow");
+
+        // Report paths are relative to the input, whichever revision they were read from
+        let report = |revision: &str| {
+            let cli = Cli::parse_from(["summarize", root.to_str().unwrap(), "--rev", revision]);
+            count_tokens(&cli, &Session::from_cli(&cli).unwrap()).unwrap()
+        };
+        let diff = TokenDiff::between(&report("HEAD~1"), &report("HEAD"));
+        let summary: Vec<_> = diff
+            .files
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("main.rs", DiffStatus::Grown),
+                ("lib.rs", DiffStatus::Added)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_histories() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("hot.rs"), "1\n").unwrap();
+        std::fs::write(dir.path().join("cold.rs"), "1\n").unwrap();
+        commit_all(&repo, "initial");
+        std::fs::write(dir.path().join("hot.rs"), "2\n").unwrap();
+        commit_all(&repo, "change");
+
+        // A second author
+        std::fs::write(dir.path().join("hot.rs"), "3\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("hot.rs")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Other", "other@example.com").unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "other",
+            &tree,
+            &[&parent],
+        )
+        .unwrap();
+
+        let histories = file_histories(&repo, "HEAD").unwrap();
+        let hot = histories[Path::new("hot.rs")];
+        assert_eq!((hot.commits, hot.authors), (3, 2));
+        let cold = histories[Path::new("cold.rs")];
+        assert_eq!((cold.commits, cold.authors), (1, 1));
+        assert!(hot.last_modified >= cold.last_modified);
+
+        let histories = file_histories(&repo, "HEAD~1").unwrap();
+        assert_eq!(histories[Path::new("hot.rs")].commits, 2);
+    }
+
+    #[test]
+    fn test_multiple_repositories() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let roots = [first.path().to_path_buf(), second.path().to_path_buf()];
+        for root in &roots {
+            let repo = Repository::init(root).unwrap();
+            std::fs::write(root.join("kept.rs"), "1\n").unwrap();
+            std::fs::write(root.join("edited.rs"), "1\n").unwrap();
+            commit_all(&repo, "initial");
+            std::fs::write(root.join("edited.rs"), "2\n").unwrap();
+        }
+        let files: Vec<PathBuf> = roots
+            .iter()
+            .flat_map(|root| [root.join("kept.rs"), root.join("edited.rs")])
+            .collect();
+
+        // Each input is compared against its own repository
+        assert_eq!(
+            filter_changed(files, &roots, &GitSelection::Uncommitted).unwrap(),
+            vec![roots[0].join("edited.rs"), roots[1].join("edited.rs")]
+        );
+
+        let histories = FileHistories::collect(roots.iter().map(PathBuf::as_path), "HEAD").unwrap();
+        for root in &roots {
+            assert_eq!(histories.get(&root.join("kept.rs")).unwrap().commits, 1);
+        }
+        assert!(histories.get(Path::new("/elsewhere/kept.rs")).is_none());
+    }
+}
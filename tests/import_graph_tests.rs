@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use summarize::import_graph::ImportGraph;
+    use summarize::session::Session;
+    use summarize::syntax_tree::{expand_use, imports, Import};
+
+    fn write_files(root: &Path, files: &[(&str, &str)]) -> Vec<PathBuf> {
+        files
+            .iter()
+            .map(|(name, content)| {
+                let path = root.join(name);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, content).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    fn names(root: &Path, files: Vec<PathBuf>) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_imports() {
+        assert_eq!(
+            expand_use("crate::{a::b, c::{self, d as e}, f::*}"),
+            vec![
+                vec!["crate", "a", "b"],
+                vec!["crate", "c"],
+                vec!["crate", "c", "d"],
+                vec!["crate", "f"],
+            ]
+        );
+
+        let python = "import a.b as c\nfrom ..pkg import x, y as z\n";
+        assert_eq!(
+            imports(python, "python"),
+            vec![
+                Import::Python {
+                    level: 0,
+                    module: vec!["a".to_string(), "b".to_string()],
+                    names: vec![],
+                },
+                Import::Python {
+                    level: 2,
+                    module: vec!["pkg".to_string()],
+                    names: vec!["x".to_string(), "y".to_string()],
+                },
+            ]
+        );
+
+        let script = "import a from './a';\nexport * from \"../b\";\nconst c = require('c');\n";
+        assert_eq!(
+            imports(script, "typescript"),
+            vec![
+                Import::Path("./a".to_string()),
+                Import::Path("../b".to_string()),
+                Import::Path("c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reachable_rust_and_python() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let files = write_files(
+            root,
+            &[
+                ("Cargo.toml", "[package]\nname = \"my-tool\"\n"),
+                ("src/main.rs", "use my_tool::cli::Cli;\nfn main() {}\n"),
+                ("src/lib.rs", "pub mod cli;\n"),
+                ("src/cli.rs", "use crate::models::{Model, Other};\n"),
+                ("src/models/mod.rs", "mod inner { mod deep; }\n"),
+                ("src/models/inner/deep.rs", "use super::super::helper;\n"),
+                ("src/unused.rs", "use anyhow::Result;\n"),
+                ("app/main.py", "from .util import helper\nimport os\n"),
+                ("app/util.py", "from app import config\n"),
+                ("app/config.py", ""),
+                ("app/other.py", ""),
+            ],
+        );
+
+        let session = Session::default();
+        let mut graph = ImportGraph::new(&files, &session).unwrap();
+        assert_eq!(
+            names(
+                root,
+                graph.reachable(&[root.join("src/main.rs")], None).unwrap()
+            ),
+            vec![
+                "src/main.rs",
+                "src/lib.rs",
+                "src/cli.rs",
+                "src/models/mod.rs",
+                "src/models/inner/deep.rs"
+            ]
+        );
+        assert_eq!(
+            names(
+                root,
+                graph
+                    .reachable(&[root.join("src/main.rs")], Some(1))
+                    .unwrap()
+            ),
+            vec!["src/main.rs", "src/lib.rs", "src/cli.rs"]
+        );
+        assert_eq!(
+            names(
+                root,
+                graph.reachable(&[root.join("app/main.py")], None).unwrap()
+            ),
+            vec!["app/main.py", "app/util.py", "app/config.py"]
+        );
+        assert!(graph.reachable(&[root.join("missing.rs")], None).is_err());
+    }
+
+    #[test]
+    fn test_reachable_scripts_and_go() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let files = write_files(
+            root,
+            &[
+                (
+                    "web/index.ts",
+                    "import { a } from './lib/a.js';\nimport './lib';\n",
+                ),
+                ("web/lib/a.ts", "import React from 'react';\n"),
+                ("web/lib/index.tsx", ""),
+                ("web/unused.ts", ""),
+                ("go.mod", "module example.com/app\n\ngo 1.22\n"),
+                (
+                    "cmd/main.go",
+                    "package main\n\nimport \"example.com/app/store\"\n",
+                ),
+                ("cmd/flags.go", "package main\n"),
+                ("store/store.go", "package store\n\nimport \"fmt\"\n"),
+                ("store/store_test.go", "package store\n"),
+                ("other/other.go", "package other\n"),
+            ],
+        );
+
+        let session = Session::default();
+        let mut graph = ImportGraph::new(&files, &session).unwrap();
+        assert_eq!(
+            names(
+                root,
+                graph.reachable(&[root.join("web/index.ts")], None).unwrap()
+            ),
+            vec!["web/index.ts", "web/lib/a.ts", "web/lib/index.tsx"]
+        );
+        assert_eq!(
+            names(
+                root,
+                graph.reachable(&[root.join("cmd/main.go")], None).unwrap()
+            ),
+            vec!["cmd/main.go", "cmd/flags.go", "store/store.go"]
+        );
+    }
+}
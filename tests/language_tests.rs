@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use summarize::languages::{detect_language, LanguageOverride, LanguageOverrides};
+
+    #[test]
+    fn test_detect_language() {
+        // Extensions, case-insensitively
+        assert_eq!(
+            detect_language(Path::new("src/main.rs"), None),
+            Some("rust")
+        );
+        assert_eq!(detect_language(Path::new("App.TSX"), None), Some("tsx"));
+        assert_eq!(detect_language(Path::new("notes.txt"), None), None);
+
+        // Well-known file names
+        assert_eq!(
+            detect_language(Path::new("docker/Dockerfile"), None),
+            Some("dockerfile")
+        );
+        assert_eq!(
+            detect_language(Path::new("Dockerfile.dev"), None),
+            Some("dockerfile")
+        );
+        assert_eq!(
+            detect_language(Path::new("Makefile"), None),
+            Some("makefile")
+        );
+        assert_eq!(
+            detect_language(Path::new("CMakeLists.txt"), None),
+            Some("cmake")
+        );
+
+        // Shebangs for extensionless scripts
+        let script = Path::new("bin/tool");
+        assert_eq!(
+            detect_language(script, Some("#!/usr/bin/env python3\nprint(1)\n")),
+            Some("python")
+        );
+        assert_eq!(
+            detect_language(script, Some("#!/usr/bin/env -S node --no-warnings\n")),
+            Some("javascript")
+        );
+        assert_eq!(detect_language(script, Some("#!/bin/sh\n")), Some("bash"));
+        assert_eq!(detect_language(script, Some("plain text\n")), None);
+
+        // Modelines beat the extension
+        assert_eq!(
+            detect_language(
+                Path::new("build.conf"),
+                Some("x = 1\n# vim: set ft=python:\n")
+            ),
+            Some("python")
+        );
+        assert_eq!(
+            detect_language(script, Some("# -*- mode: ruby; coding: utf-8 -*-\n")),
+            Some("ruby")
+        );
+        assert_eq!(detect_language(script, Some("index: ft=python\n")), None);
+    }
+
+    #[test]
+    fn test_language_override() {
+        let rule = LanguageOverride::parse("*.tpl=jinja").unwrap();
+        assert!(rule.matches(Path::new("templates/page.tpl")));
+        assert!(!rule.matches(Path::new("templates/page.html")));
+
+        let rule = LanguageOverride::parse("ci/**/*.yml=github-actions").unwrap();
+        assert!(rule.matches(Path::new("ci/workflows/build.yml")));
+        assert!(!rule.matches(Path::new("config/build.yml")));
+
+        assert!(LanguageOverride::parse("*.tpl").is_err());
+        assert!(LanguageOverride::parse("=jinja").is_err());
+
+        // The first matching rule wins over detection, which still covers everything else
+        let overrides = LanguageOverrides::new(vec![
+            LanguageOverride::parse("*.rs=text").unwrap(),
+            LanguageOverride::parse("main.*=c").unwrap(),
+        ]);
+        assert_eq!(
+            overrides.detect(Path::new("src/main.rs"), None),
+            Some("text")
+        );
+        assert_eq!(overrides.detect(Path::new("main.go"), None), Some("c"));
+        assert_eq!(overrides.detect(Path::new("lib.py"), None), Some("python"));
+        assert_eq!(
+            detect_language(Path::new("src/main.rs"), None),
+            Some("rust")
+        );
+    }
+}
@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use std::io::{Cursor, Read};
+    use summarize::llm::{streamed_json, CODEBASE};
+
+    #[test]
+    fn test_streamed_json() {
+        let request = json!({
+            "model": "test",
+            "messages": [{"text": format!("Prompt\n\n{}", CODEBASE)}],
+        });
+        let codebase = "fn main() {\n\tprintln!(\"{}\\n\", \"héllo ✓\");\u{1}\r\n}\n";
+
+        let mut body = String::new();
+        streamed_json(&request, Cursor::new(codebase))
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+
+        let parsed: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed["model"], "test");
+        assert_eq!(
+            parsed["messages"][0]["text"],
+            format!("Prompt\n\n{}", codebase)
+        );
+        // A request without the marker can't take the codebase
+        assert!(streamed_json(&json!({"text": "Prompt"}), Cursor::new("")).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use summarize::syntax_tree::outline;
+
+    #[test]
+    fn test_outline_rust() {
+        let source = r#"use std::fmt;
+
+/// A point
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+}
+
+// Not attached
+
+impl Point {
+    /// Creates a point
+    pub fn new(x: i32) -> Self {
+        let closure = || Point { x };
+        closure()
+    }
+}
+
+fn main() {
+    println!("{}", Point::new(1).x);
+}
+"#;
+        assert_eq!(
+            outline(source, "rust").unwrap(),
+            r#"/// A point
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+}
+
+impl Point {
+    /// Creates a point
+    pub fn new(x: i32) -> Self { ... }
+}
+
+fn main() { ... }
+"#
+        );
+    }
+
+    #[test]
+    fn test_outline_python_and_typescript() {
+        let python = r#""""Module docs."""
+import os
+
+class Store:
+    """Keeps things."""
+    size: int = 0
+
+    def get(self, key):
+        """Looks up a key."""
+        return os.environ[key]
+
+def helper(): return 1
+
+if __name__ == "__main__":
+    helper()
+"#;
+        assert_eq!(
+            outline(python, "python").unwrap(),
+            r#""""Module docs."""
+
+class Store:
+    """Keeps things."""
+    size: int = 0
+
+    def get(self, key):
+        """Looks up a key."""
+        ...
+
+def helper(): ...
+"#
+        );
+
+        // A shebang or license comment before the module docstring keeps it a docstring
+        let script =
+            "#!/usr/bin/env python\n\"\"\"Module docs.\"\"\"\nimport os\n\"\"\"Not docs.\"\"\"\n";
+        assert_eq!(
+            outline(script, "python").unwrap(),
+            "#!/usr/bin/env python\n\"\"\"Module docs.\"\"\"\n"
+        );
+
+        let typescript = r#"import { x } from "./x";
+// Options for run
+export interface Options { verbose: boolean }
+export const run = (options: Options): number => {
+  return x(options);
+};
+const limit = 10;
+"#;
+        assert_eq!(
+            outline(typescript, "typescript").unwrap(),
+            r#"// Options for run
+export interface Options { verbose: boolean }
+export const run = (options: Options): number => { ... };
+"#
+        );
+    }
+
+    #[test]
+    fn test_outline_fallback() {
+        // Unsupported languages and files without declarations keep their content
+        assert_eq!(outline("puts 1\n", "ruby"), None);
+        assert_eq!(outline("console.log(1);\n", "javascript"), None);
+        assert_eq!(
+            outline("package main\n\nfunc main() {\n\tprintln(1)\n}\n", "go").unwrap(),
+            "package main\n\nfunc main() { ... }\n"
+        );
+    }
+}
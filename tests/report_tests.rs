@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use summarize::models::{
+        BudgetViolation, DiffStatus, FileStats, TokenBudget, TokenDiff, TokenReport, TokenizerModel,
+    };
+    use summarize::report_path;
+    use summarize::tokenizers::PricingTable;
+
+    fn sample_report() -> TokenReport {
+        let mut report = TokenReport::new();
+        report.add_file(
+            PathBuf::from("src/main.rs"),
+            FileStats {
+                tokens: 120,
+                bytes: 480,
+                lines: 20,
+            },
+        );
+        report.add_file(
+            PathBuf::from("docs/a,b.md"),
+            FileStats {
+                tokens: 30,
+                bytes: 100,
+                lines: 4,
+            },
+        );
+        report.set_model(&TokenizerModel::Gpt4, &PricingTable::default());
+        report
+    }
+
+    #[test]
+    fn test_report_totals() {
+        let mut report = sample_report();
+        assert_eq!(report.total_tokens, 150);
+        assert_eq!(report.total_bytes, 580);
+        assert_eq!(report.total_lines, 24);
+
+        // Re-adding a file replaces its previous counts
+        report.add_file(
+            PathBuf::from("src/main.rs"),
+            FileStats {
+                tokens: 10,
+                bytes: 40,
+                lines: 2,
+            },
+        );
+        assert_eq!(report.total_tokens, 40);
+        assert_eq!(report.files.len(), 2);
+    }
+
+    #[test]
+    fn test_report_json() {
+        let report = sample_report();
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["total_tokens"], 150);
+        assert_eq!(json["files"]["src/main.rs"]["lines"], 20);
+        assert_eq!(json["model"], "GPT-4");
+        assert_eq!(json["tokenizer"], "cl100k_base");
+        assert_eq!(json["cost"]["input_cost_per_1k"], 0.03);
+    }
+
+    #[test]
+    fn test_report_csv() {
+        let csv = sample_report().to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "path,tokens,bytes,lines");
+        assert_eq!(lines[1], "\"docs/a,b.md\",30,100,4");
+        assert_eq!(lines[2], "src/main.rs,120,480,20");
+        assert_eq!(lines[3], "TOTAL,150,580,24");
+    }
+
+    #[test]
+    fn test_token_budget() {
+        let report = sample_report();
+
+        let within = TokenBudget {
+            max_total: Some(150),
+            max_file: Some(120),
+            max_dir: Some(120),
+        };
+        assert!(within.check(&report, &[]).is_empty());
+
+        let exceeded = TokenBudget {
+            max_total: Some(100),
+            max_file: None,
+            max_dir: Some(50),
+        };
+        let violations = exceeded.check(&report, &[]);
+        assert_eq!(
+            violations,
+            vec![
+                BudgetViolation::Total {
+                    tokens: 150,
+                    limit: 100
+                },
+                BudgetViolation::Directory {
+                    path: PathBuf::from("src"),
+                    tokens: 120,
+                    limit: 50
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_directory_tokens() {
+        let stats = FileStats {
+            tokens: 10,
+            bytes: 40,
+            lines: 2,
+        };
+        let mut report = TokenReport::new();
+        for path in [
+            "./src/main.rs",
+            "./src/models/a.rs",
+            "./build.rs",
+            "/work/app/lib/b.rs",
+            "/work/app/c.rs",
+        ] {
+            report.add_file(PathBuf::from(path), stats);
+        }
+
+        // The input roots themselves are left out, and nothing rolls up past them
+        let roots = [PathBuf::from("."), PathBuf::from("/work/app")];
+        assert_eq!(
+            report.directory_tokens(&roots),
+            [
+                (PathBuf::from("./src"), 20),
+                (PathBuf::from("./src/models"), 10),
+                (PathBuf::from("/work/app/lib"), 10),
+            ]
+            .into()
+        );
+
+        let budget = TokenBudget {
+            max_total: None,
+            max_file: None,
+            max_dir: Some(15),
+        };
+        assert_eq!(
+            budget.check(&report, &roots),
+            vec![BudgetViolation::Directory {
+                path: PathBuf::from("./src"),
+                tokens: 20,
+                limit: 15
+            }]
+        );
+    }
+
+    #[test]
+    fn test_report_path() {
+        let root = [PathBuf::from("/work/app")];
+        assert_eq!(
+            report_path(Path::new("/work/app/src/main.rs"), &root),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            report_path(Path::new("/work/app/../app/./lib.rs"), &root),
+            PathBuf::from("lib.rs")
+        );
+        assert_eq!(
+            report_path(Path::new("/elsewhere/x.rs"), &root),
+            PathBuf::from("/elsewhere/x.rs")
+        );
+
+        // Several inputs keep their names apart; a file input is recorded by its name
+        let roots = [PathBuf::from("/work/app"), PathBuf::from("/work/app/src")];
+        assert_eq!(
+            report_path(Path::new("/work/app/src/main.rs"), &roots),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            report_path(Path::new("/work/app/README.md"), &roots),
+            PathBuf::from("app/README.md")
+        );
+        assert_eq!(
+            report_path(
+                Path::new("/work/notes.txt"),
+                &[PathBuf::from("/work/notes.txt")]
+            ),
+            PathBuf::from("notes.txt")
+        );
+    }
+
+    #[test]
+    fn test_token_diff() {
+        let old = sample_report();
+        let mut new = TokenReport::new();
+        new.add_file(
+            PathBuf::from("src/main.rs"),
+            FileStats {
+                tokens: 100,
+                ..Default::default()
+            },
+        );
+        new.add_file(
+            PathBuf::from("src/lib.rs"),
+            FileStats {
+                tokens: 500,
+                ..Default::default()
+            },
+        );
+
+        let diff = TokenDiff::between(&old, &new);
+        assert_eq!(diff.delta, 450);
+        assert_eq!(diff.unchanged, 0);
+
+        let summary: Vec<_> = diff
+            .files
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.status, f.delta))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("src/lib.rs", DiffStatus::Added, 500),
+                ("docs/a,b.md", DiffStatus::Removed, -30),
+                ("src/main.rs", DiffStatus::Shrunk, -20),
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use summarize::syntax_tree::SymbolIndex;
+
+    fn index() -> SymbolIndex {
+        let llm = r#"use crate::models::Model;
+
+/// Sends a prompt
+pub fn summarize(prompt: &str, model: Model) -> String {
+    let client = Client::new();
+    client.send(prompt, model)
+}
+
+pub struct Client;
+
+impl Client {
+    pub fn new() -> Self {
+        Client
+    }
+
+    pub fn send(&self, prompt: &str, model: Model) -> String {
+        format_prompt(prompt)
+    }
+}
+
+fn format_prompt(prompt: &str) -> String {
+    prompt.trim().to_string()
+}
+
+fn unrelated() {}
+"#;
+        let models = "pub enum Model {\n    Small,\n    Large,\n}\n";
+        let python = r#"class Store:
+    def get(self, key):
+        return self.load(key)
+
+    def load(self, key):
+        return key
+"#;
+        SymbolIndex::build(&[
+            (PathBuf::from("src/llm/mod.rs"), llm.to_string(), "rust"),
+            (PathBuf::from("src/models.rs"), models.to_string(), "rust"),
+            (PathBuf::from("app/store.py"), python.to_string(), "python"),
+        ])
+    }
+
+    fn lines(symbols: &[&summarize::syntax_tree::Symbol]) -> Vec<String> {
+        symbols
+            .iter()
+            .map(|symbol| {
+                format!(
+                    "{}:{}-{}",
+                    symbol.path.display(),
+                    symbol.start_line,
+                    symbol.end_line
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_symbols() {
+        let index = index();
+
+        let found = index.find("crate::llm::summarize");
+        assert_eq!(lines(&found), vec!["src/llm/mod.rs:3-7"]);
+        assert!(found[0]
+            .text
+            .starts_with("/// Sends a prompt\npub fn summarize("));
+
+        assert_eq!(
+            index.find("Client::send")[0].qualified,
+            vec!["crate", "llm", "Client", "send"]
+        );
+        assert_eq!(
+            lines(&index.find("store.Store.get")),
+            vec!["app/store.py:2-3"]
+        );
+        // Segments in between may be skipped, as with re-exports
+        assert_eq!(
+            lines(&index.find("crate::Model")),
+            vec!["src/models.rs:1-4"]
+        );
+        assert!(index.find("crate::other::summarize").is_empty());
+    }
+
+    #[test]
+    fn test_select_dependencies() {
+        let index = index();
+
+        let direct = index.select(&["summarize".to_string()], 0).unwrap();
+        assert_eq!(lines(&direct), vec!["src/llm/mod.rs:3-7"]);
+
+        let selected = index.select(&["summarize".to_string()], 1).unwrap();
+        assert_eq!(
+            lines(&selected),
+            vec![
+                "src/llm/mod.rs:3-7",
+                "src/models.rs:1-4",
+                "src/llm/mod.rs:9-9",
+                "src/llm/mod.rs:12-14",
+                "src/llm/mod.rs:16-18",
+            ]
+        );
+
+        let transitive = index.select(&["summarize".to_string()], 2).unwrap();
+        assert_eq!(transitive.len(), 6);
+        assert_eq!(transitive[5].name, "format_prompt");
+
+        // Methods inside a selected class aren't repeated
+        let class = index.select(&["Store".to_string()], 2).unwrap();
+        assert_eq!(lines(&class), vec!["app/store.py:1-6"]);
+
+        assert!(index.select(&["missing".to_string()], 1).is_err());
+    }
+
+    #[test]
+    fn test_declarations_are_not_references() {
+        let lib = r#"pub struct Message {
+    pub text: String,
+}
+
+pub fn send(text: &str) -> Vec<Message> {
+    let body = text.to_string();
+    vec![Message { text: body }]
+}
+"#;
+        let python = "def handle(text, limit=10):\n    for line in text:\n        print(line)\n";
+        // Each of these shares a name with a field, parameter or local above
+        let other = "fn text() {}\nfn body() {}\nfn line() {}\n";
+        let index = SymbolIndex::build(&[
+            (PathBuf::from("src/lib.rs"), lib.to_string(), "rust"),
+            (PathBuf::from("src/other.rs"), other.to_string(), "rust"),
+            (PathBuf::from("app/handle.py"), python.to_string(), "python"),
+        ]);
+
+        let selected = index.select(&["send".to_string()], 2).unwrap();
+        assert_eq!(lines(&selected), vec!["src/lib.rs:5-8", "src/lib.rs:1-3"]);
+        let selected = index.select(&["handle".to_string()], 2).unwrap();
+        assert_eq!(lines(&selected), vec!["app/handle.py:1-3"]);
+    }
+}
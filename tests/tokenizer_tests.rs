@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use summarize::models::TokenizerModel;
-    use summarize::tokenizers;
+    use summarize::models::{ContextFit, TokenizerModel};
+    use summarize::tokenizers::{self, load_pricing, PricingTable};
 
     #[test]
     fn test_tokenizer_name() {
@@ -18,17 +18,81 @@ mod tests {
 
     #[test]
     fn test_token_cost() {
+        let pricing = PricingTable::default();
+
         // Test GPT costs
-        let (input_cost, output_cost) = tokenizers::get_token_cost(&TokenizerModel::Gpt35Turbo, 0);
-        assert_eq!(input_cost, 0.0010);
-        assert_eq!(output_cost, 0.0020);
+        let (input_cost, output_cost) =
+            tokenizers::get_token_cost(&TokenizerModel::Gpt35Turbo, 0, &pricing);
+        assert_eq!(input_cost, 0.0005);
+        assert_eq!(output_cost, 0.0015);
 
         // Test Claude costs
-        let (input_cost, output_cost) = tokenizers::get_token_cost(&TokenizerModel::Claude3Opus, 0);
+        let (input_cost, output_cost) =
+            tokenizers::get_token_cost(&TokenizerModel::Claude3Opus, 0, &pricing);
         assert_eq!(input_cost, 0.015);
         assert_eq!(output_cost, 0.075);
     }
 
+    #[test]
+    fn test_tiered_token_cost() {
+        let pricing = PricingTable::default();
+
+        // Gemini 1.5 prices double once the prompt exceeds 128k tokens
+        let short = tokenizers::get_token_cost(&TokenizerModel::Gemini15Pro, 128_000, &pricing);
+        let long = tokenizers::get_token_cost(&TokenizerModel::Gemini15Pro, 128_001, &pricing);
+        assert_eq!(short, (0.00125, 0.005));
+        assert_eq!(long, (0.0025, 0.01));
+
+        assert_eq!(
+            tokenizers::get_cached_input_cost(&TokenizerModel::Claude3Opus, 0, &pricing),
+            Some(0.0015)
+        );
+        assert_eq!(
+            tokenizers::get_batch_discount(&TokenizerModel::Gpt4, &pricing),
+            Some(0.5)
+        );
+    }
+
+    #[test]
+    fn test_pricing_override() {
+        let mut table = PricingTable::default_table();
+        let custom = PricingTable::from_json(
+            r#"{"models": {"gpt4": {"tiers": [{"input": 1.0, "output": 2.0}]}}}"#,
+        )
+        .unwrap();
+        table.merge(custom);
+
+        let gpt4 = table.get(&TokenizerModel::Gpt4).unwrap();
+        assert_eq!(gpt4.tier_for(1_000_000).unwrap().input, 1.0);
+        assert_eq!(gpt4.batch_discount, None);
+
+        // Other models keep their built-in prices
+        assert!(table.get(&TokenizerModel::Claude3Opus).is_some());
+
+        assert!(PricingTable::from_json(r#"{"models": {"gpt4": {"tiers": []}}}"#).is_err());
+    }
+
+    #[test]
+    fn test_load_pricing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let custom = dir.path().join("pricing.json");
+        std::fs::write(
+            &custom,
+            r#"{"models": {"gpt4": {"tiers": [{"input": 1.0, "output": 2.0}]}}}"#,
+        )
+        .unwrap();
+        let table = load_pricing(Some(&custom)).unwrap();
+        assert_eq!(
+            tokenizers::get_token_cost(&TokenizerModel::Gpt4, 0, &table),
+            (0.001, 0.002)
+        );
+
+        // A --pricing file that can't be used is an error rather than a silent fallback
+        std::fs::write(&custom, "{not json").unwrap();
+        assert!(load_pricing(Some(&custom)).is_err());
+        assert!(load_pricing(Some(&dir.path().join("missing.json"))).is_err());
+    }
+
     #[test]
     fn test_token_counting() {
         // Test with a simple string
@@ -44,4 +108,33 @@ mod tests {
             tokenizers::count_tokens(&longer_text, &TokenizerModel::Gpt35Turbo);
         assert!(longer_token_count > token_count);
     }
+
+    #[test]
+    fn test_context_fit() {
+        let pricing = PricingTable::default();
+        let fit = ContextFit::for_model(&TokenizerModel::Gpt4, 10_000, &pricing);
+        assert_eq!(fit.context_window, 8_192);
+        assert!(!fit.fits);
+        assert_eq!(fit.headroom, 0);
+
+        let fit = ContextFit::for_model(&TokenizerModel::Claude3Opus, 50_000, &pricing);
+        assert!(fit.fits);
+        assert_eq!(fit.percent_used, 25.0);
+        assert_eq!(fit.headroom, 150_000);
+
+        // One entry per model in the catalog, each with its own tokenizer's count
+        let tokens = [("cl100k_base", 100), ("p50k_base", 120)].into();
+        let fits = ContextFit::for_all_models(&tokens, &pricing);
+        assert_eq!(fits.len(), 13);
+        let tokens_for = |model: &TokenizerModel| {
+            let name = model.to_string();
+            fits.iter().find(|fit| fit.model == name).unwrap().tokens
+        };
+        assert_eq!(tokens_for(&TokenizerModel::Gpt4), 100);
+        assert_eq!(tokens_for(&TokenizerModel::Claude3Opus), 120);
+        assert_eq!(
+            tokenizers::tokenizer_names(),
+            vec!["cl100k_base", "p50k_base"]
+        );
+    }
 }
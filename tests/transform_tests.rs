@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use summarize::languages::LanguageOverrides;
+    use summarize::models::Transform;
+    use summarize::transforms::{
+        apply_transforms, collapse_blank_lines, comment_syntax, strip_comments,
+        strip_license_header, trim_trailing_whitespace,
+    };
+
+    #[test]
+    fn test_strip_comments_keeps_literals() {
+        let rust = comment_syntax("rust").unwrap();
+        let input = "/// Docs\nfn f<'a>(s: &'a str) {\n    let url = \"http://x\"; // note\n    let c = '\"'; /* a /* nested */ b */ let r = r#\"/* raw \"# ;\n}\n";
+        assert_eq!(
+            strip_comments(input, rust),
+            "fn f<'a>(s: &'a str) {\n    let url = \"http://x\";\n    let c = '\"';  let r = r#\"/* raw \"# ;\n}\n"
+        );
+
+        let python = comment_syntax("python").unwrap();
+        assert_eq!(
+            strip_comments("x = '#fff'  # color\n\"\"\"doc\n# kept\"\"\"\n", python),
+            "x = '#fff'\n\"\"\"doc\n# kept\"\"\"\n"
+        );
+
+        let shell = comment_syntax("bash").unwrap();
+        assert_eq!(
+            strip_comments("#!/bin/sh\n# setup\necho \"a # b\" ${#x} # done\n", shell),
+            "#!/bin/sh\necho \"a # b\" ${#x}\n"
+        );
+
+        let perl = comment_syntax("perl").unwrap();
+        assert_eq!(
+            strip_comments("my $last = $#a; # last index\n", perl),
+            "my $last = $#a;\n"
+        );
+
+        let javascript = comment_syntax("javascript").unwrap();
+        assert_eq!(
+            strip_comments(
+                "const re = /https?:\\/\\//; // url\nif (x) return /[/*]+/g.test(y) / 2; /* z */\n",
+                javascript
+            ),
+            "const re = /https?:\\/\\//;\nif (x) return /[/*]+/g.test(y) / 2;\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_license_header() {
+        let go = comment_syntax("go").unwrap();
+        assert_eq!(
+            strip_license_header("/*\n * Copyright 2024 Example\n */\n\npackage main\n", go),
+            "package main\n"
+        );
+        assert_eq!(
+            strip_license_header("// SPDX-License-Identifier: MIT\n\n\tx := 1\n", go),
+            "\tx := 1\n"
+        );
+
+        // Other leading comments stay
+        let docs = "// Package main runs things\npackage main\n";
+        assert_eq!(strip_license_header(docs, go), docs);
+
+        let python = comment_syntax("python").unwrap();
+        assert_eq!(
+            strip_license_header("#!/usr/bin/env python\n# License: MIT\nimport os\n", python),
+            "#!/usr/bin/env python\nimport os\n"
+        );
+    }
+
+    #[test]
+    fn test_whitespace_transforms() {
+        assert_eq!(trim_trailing_whitespace("a  \nb\t\nc "), "a\nb\nc");
+        assert_eq!(collapse_blank_lines("\n\na\n\n \n\nb\n"), "a\n\nb\n");
+
+        // Unknown languages still get whitespace transforms
+        let all = [
+            Transform::Comments,
+            Transform::License,
+            Transform::TrailingWhitespace,
+            Transform::BlankLines,
+        ];
+        assert_eq!(
+            apply_transforms(
+                "# heading  \n\n\ntext\n",
+                Path::new("notes.txt"),
+                &all,
+                &LanguageOverrides::default()
+            ),
+            "# heading\n\ntext\n"
+        );
+        assert_eq!(
+            apply_transforms(
+                "x = 1  # one\n\n\n\ny = 2\n",
+                Path::new("a.py"),
+                &all,
+                &LanguageOverrides::default()
+            ),
+            "x = 1\n\ny = 2\n"
+        );
+    }
+}
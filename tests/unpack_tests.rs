@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use std::path::{Path, PathBuf};
+    use summarize::cli::Cli;
+    use summarize::models::OutputFormat;
+    use summarize::parsers::{detect_format, parse_default, parse_markdown, ParsedFile};
+    use summarize::unpack::{apply_unpack, plan_unpack, safe_relative_path, UnpackAction};
+
+    #[test]
+    fn test_parse_default() {
+        let input = "a.txt\n---\nfirst\n---\nstill first\n\n\n---\nb.txt\n---\n\n\n---\n";
+        let files = parse_default(input).unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                ParsedFile {
+                    path: PathBuf::from("a.txt"),
+                    content: "first\n---\nstill first\n".to_string(),
+                },
+                ParsedFile {
+                    path: PathBuf::from("b.txt"),
+                    content: String::new(),
+                },
+            ]
+        );
+        assert!(matches!(detect_format(input), OutputFormat::Default));
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let input = "Here are the edits:\n\n## `src/lib.rs`\n````rust\n```\nnested\n```\n````\nsrc/main.rs\n```\nfn main() {}\n\n```\n";
+        let files = parse_markdown(input).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(files[0].content, "```\nnested\n```");
+        assert_eq!(files[1].content, "fn main() {}\n");
+        assert!(matches!(
+            detect_format("a.rs\n```rust\n"),
+            OutputFormat::Markdown
+        ));
+    }
+
+    #[test]
+    fn test_parse_git_metadata_lines() {
+        let history = "Git history: last modified 2024-01-02, 3 commits, 2 authors";
+        let input = format!(
+            "{history}\na.rs\n---\nfn a() {{}}\n\n---\n{history}\nb.rs\n---\nfn b() {{}}\n\n---\n"
+        );
+        let files = parse_default(&input).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].content, "fn a() {}");
+        assert_eq!(files[1].path, PathBuf::from("b.rs"));
+
+        let input = format!("{history}\na.rs\n```rust\nfn a() {{}}\n```\n");
+        assert!(matches!(detect_format(&input), OutputFormat::Markdown));
+        assert_eq!(
+            parse_markdown(&input).unwrap()[0].path,
+            PathBuf::from("a.rs")
+        );
+    }
+
+    #[test]
+    fn test_detect_format_from_the_start() {
+        // Content further down doesn't decide the format
+        let input = "src/main.rs\n---\nfn main() {\n    println!(\"<documents>\");\n}\n\n---\n";
+        assert!(matches!(detect_format(input), OutputFormat::Default));
+        assert_eq!(parse_default(input).unwrap().len(), 1);
+
+        // Default-format paths may start with brackets
+        for path in ["[id].tsx", "{a,b}.txt"] {
+            let input = format!("{}\n---\nexport default 1\n\n---\n", path);
+            assert!(matches!(detect_format(&input), OutputFormat::Default));
+        }
+
+        assert!(matches!(
+            detect_format("\n<documents>\n<document index=\"1\">\n"),
+            OutputFormat::Cxml
+        ));
+        assert!(matches!(
+            detect_format("[\n{\"path\":\"a.rs\",\"content\":\"\"}\n]\n"),
+            OutputFormat::Json
+        ));
+        assert!(matches!(
+            detect_format("{\"path\":\"a.rs\",\"content\":\"\"}\n"),
+            OutputFormat::Jsonl
+        ));
+        assert!(matches!(
+            detect_format("```text\nsrc\n```\n"),
+            OutputFormat::Markdown
+        ));
+
+        // An explicit format wins over detection
+        let cli = Cli::parse_from(["summarize", "--unpack", "bundle.txt", "-f", "markdown"]);
+        assert!(matches!(cli.chosen_format(), Some(OutputFormat::Markdown)));
+        let cli = Cli::parse_from(["summarize", "--unpack", "bundle.txt"]);
+        assert!(cli.chosen_format().is_none());
+    }
+
+    #[test]
+    fn test_unpack_paths() {
+        assert!(safe_relative_path(Path::new("./src/../lib.rs")).is_err());
+        assert!(safe_relative_path(Path::new("/etc/passwd")).is_err());
+        assert!(safe_relative_path(Path::new(".")).is_err());
+        assert_eq!(
+            safe_relative_path(Path::new("./src/lib.rs")).unwrap(),
+            PathBuf::from("src/lib.rs")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("same.txt"), "same").unwrap();
+        let files = vec![
+            ParsedFile {
+                path: PathBuf::from("same.txt"),
+                content: "same".to_string(),
+            },
+            ParsedFile {
+                path: PathBuf::from("nested/new.txt"),
+                content: "new".to_string(),
+            },
+        ];
+
+        let entries = plan_unpack(files, dir.path()).unwrap();
+        assert_eq!(entries[0].action, UnpackAction::Unchanged);
+        assert_eq!(entries[1].action, UnpackAction::Create);
+
+        apply_unpack(&entries, dir.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("nested/new.txt")).unwrap(),
+            "new"
+        );
+
+        // One bad path rejects the whole bundle before anything is written
+        let files = vec![ParsedFile {
+            path: PathBuf::from("../escape.txt"),
+            content: String::new(),
+        }];
+        assert!(plan_unpack(files, dir.path()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unpack_refuses_symlinks() {
+        let outside = tempfile::tempdir().unwrap();
+        let secret = outside.path().join("secret.txt");
+        std::fs::write(&secret, "secret").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(&secret, dir.path().join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("linked")).unwrap();
+
+        // A good entry before the bad one is not written either
+        for bad in ["link.txt", "linked/new.txt"] {
+            let files = vec![
+                ParsedFile {
+                    path: PathBuf::from("first.txt"),
+                    content: "first".to_string(),
+                },
+                ParsedFile {
+                    path: PathBuf::from(bad),
+                    content: "overwritten".to_string(),
+                },
+            ];
+            assert!(plan_unpack(files, dir.path()).is_err());
+        }
+        assert!(!dir.path().join("first.txt").exists());
+        assert!(!outside.path().join("new.txt").exists());
+        assert_eq!(std::fs::read_to_string(&secret).unwrap(), "secret");
+    }
+}
@@ -1,7 +1,11 @@
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
     use std::path::PathBuf;
-    use summarize::utils::should_ignore;
+    use summarize::models::PathMode;
+    use summarize::utils::{
+        anonymize_path, normalize_path, restore_paths, should_ignore, PathRenderer,
+    };
 
     #[test]
     fn test_should_ignore() {
@@ -15,7 +19,7 @@ mod tests {
         let ignore_patterns = vec!["node_modules/".to_string()];
         // For a directory, this would return true if the path is actually a directory
         // We need to mock a bit here since we're just passing a path string
-        assert!(should_ignore(&path, &ignore_patterns, false) == false);
+        assert!(!should_ignore(&path, &ignore_patterns, false));
 
         // Test file extension
         let path = PathBuf::from("test.js");
@@ -27,4 +31,85 @@ mod tests {
         let ignore_patterns: Vec<String> = vec![];
         assert!(!should_ignore(&path, &ignore_patterns, false));
     }
+
+    #[test]
+    fn test_normalize_path() {
+        let cwd = Path::new("/work/repo");
+        assert_eq!(
+            normalize_path(Path::new("./src/../lib/mod.rs"), cwd),
+            PathBuf::from("/work/repo/lib/mod.rs")
+        );
+        assert_eq!(
+            normalize_path(Path::new("../other/./a.rs"), cwd),
+            PathBuf::from("/work/other/a.rs")
+        );
+        // Absolute paths ignore the working directory
+        assert_eq!(
+            normalize_path(Path::new("/tmp/x/../y"), cwd),
+            PathBuf::from("/tmp/y")
+        );
+    }
+
+    #[test]
+    fn test_path_rendering() {
+        let key = b"a key";
+        let anonymized = anonymize_path(Path::new("/home/alice/work/src/main.rs"), key);
+        let components: Vec<_> = anonymized.iter().collect();
+        assert_eq!(components.len(), 5);
+        assert!(anonymized.to_string_lossy().ends_with(".rs"));
+        assert!(!anonymized.to_string_lossy().contains("alice"));
+        // The same key always maps a directory to the same name, another key to a different one
+        assert_eq!(
+            anonymize_path(Path::new("work/src/lib.rs"), key).parent(),
+            anonymize_path(Path::new("work/src/main.rs"), key).parent()
+        );
+        assert_ne!(
+            anonymize_path(Path::new("src"), key),
+            anonymize_path(Path::new("src"), b"another key")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let file = dir.path().join("src/main.rs");
+        std::fs::write(&file, "").unwrap();
+
+        let mut renderer =
+            PathRenderer::new(PathMode::Relative, Some(dir.path()), Some("repo")).unwrap();
+        let rendered = renderer.render(&file);
+        assert_eq!(rendered, PathBuf::from("repo/src/main.rs"));
+
+        // Files that only exist in a --rev snapshot render the same way
+        assert_eq!(
+            renderer.render(&dir.path().join("src/../docs/gone.md")),
+            PathBuf::from("repo/docs/gone.md")
+        );
+
+        let mut git_root = PathRenderer::new(PathMode::GitRoot, None, None).unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        assert_eq!(
+            git_root.render(&dir.path().join("src/deleted.rs")),
+            PathBuf::from("src/deleted.rs")
+        );
+
+        // Each renderer has its own key for anonymized names
+        let first = PathRenderer::new(PathMode::Anonymized, None, None).unwrap();
+        let second = PathRenderer::new(PathMode::Anonymized, None, None).unwrap();
+        assert_ne!(first.key(), second.key());
+
+        let answer = "Start with repo/src/main.rs.";
+        assert_eq!(
+            restore_paths(answer, renderer.mapping()),
+            format!("Start with {}.", file.display())
+        );
+
+        // Only whole paths are restored, not ones inside longer names or paths
+        let mapping = [("1a2b3c.rs".to_string(), "main.rs".to_string())].into();
+        assert_eq!(
+            restore_paths(
+                "1a2b3c.rs, x/1a2b3c.rs, 1a2b3c.rsx and (1a2b3c.rs).",
+                &mapping
+            ),
+            "main.rs, x/1a2b3c.rs, 1a2b3c.rsx and (main.rs)."
+        );
+    }
 }